@@ -0,0 +1,82 @@
+// Compares sampling with a fresh sysinfo state every tick, like the fetcher
+// used to, against refreshing one long-lived sampler.
+//
+//   cargo bench --bench system_sampler
+
+use std::{
+  collections::HashSet,
+  hint::black_box,
+  time::{Duration, Instant},
+};
+
+use miyabi_widgets_lib::utils::{
+  system::{get_cpu_info, get_disk_info, get_ram_info, SampleIntervals, SystemSampler},
+  widget::SystemMetric,
+};
+
+const ITERATIONS: u32 = 20;
+
+// `pause` runs between the timed samples, like the wait between two ticks.
+fn bench(name: &str, pause: Duration, mut f: impl FnMut()) {
+  // warm up the caches sysinfo reads from
+  f();
+
+  let mut elapsed = Duration::ZERO;
+  for _ in 0..ITERATIONS {
+    std::thread::sleep(pause);
+
+    let start = Instant::now();
+    f();
+    elapsed += start.elapsed();
+  }
+
+  println!(
+    "{:<24} {:>10.2?} per sample ({} samples)",
+    name,
+    elapsed / ITERATIONS,
+    ITERATIONS
+  );
+}
+
+fn main() {
+  // the GPU is left out, it needs an NVIDIA driver and dominates the timings
+  let metrics: HashSet<SystemMetric> = [SystemMetric::Cpu, SystemMetric::Ram, SystemMetric::Disk]
+    .into_iter()
+    .collect();
+
+  bench("fresh state", Duration::ZERO, || {
+    let mut sys = sysinfo::System::new_all();
+    sys.refresh_all();
+    let mut disks = sysinfo::Disks::new();
+    disks.refresh_list();
+
+    black_box((
+      get_cpu_info(&sys),
+      get_ram_info(&sys),
+      get_disk_info(&disks),
+    ));
+  });
+
+  // every interval is zero so each metric is refreshed on every sample
+  let intervals = SampleIntervals {
+    cpu: Duration::ZERO,
+    ram: Duration::ZERO,
+    disk: Duration::ZERO,
+    network: Duration::ZERO,
+    gpu: Duration::ZERO,
+    sensors: Duration::ZERO,
+  };
+  let mut sampler = SystemSampler::new(intervals);
+  // keeps the CPU refreshes apart so the wait in `refresh` isn't timed
+  let pause = sysinfo::MINIMUM_CPU_UPDATE_INTERVAL;
+  bench("long-lived sampler", pause, || {
+    sampler.refresh_due(&metrics, Instant::now());
+    black_box(sampler.machine_info(&metrics));
+  });
+
+  let mut sampler = SystemSampler::new(SampleIntervals::default());
+  bench("default intervals", pause, || {
+    sampler.refresh_due(&metrics, Instant::now());
+    black_box(sampler.machine_info(&metrics));
+  });
+}
@@ -1,6 +1,20 @@
-use tauri::{LogicalSize, Manager, Runtime};
+use std::{collections::HashMap, path::Path, sync::Mutex};
 
-use crate::utils::{widget::Widget, widget_handler::WidgetHandler};
+use tauri::{Manager, Runtime};
+
+use crate::utils::{
+  appearance,
+  battery::{self, BatteryInfo},
+  bundle::ImportMode,
+  error::WidgetError,
+  history::{Downsample, MetricSeries, Point},
+  processes::{self, ProcessInfo, ProcessSort},
+  profile::ProfileStore,
+  settings::Settings,
+  subscription::MetricSubscription,
+  widget::Widget,
+  widget_handler::WidgetHandler,
+};
 
 #[tauri::command]
 pub fn greet(name: &str) -> String {
@@ -12,12 +26,360 @@ pub fn greet(name: &str) -> String {
 #[tauri::command]
 pub async fn get_widget_config<R: Runtime>(
   app: tauri::AppHandle<R>,
-  window: tauri::Window<R>,
+  _window: tauri::Window<R>,
+  widget_id: String,
+) -> Result<Widget, WidgetError> {
+  let widget_handler = app.state::<Mutex<WidgetHandler>>();
+
+  let widget = widget_handler
+    .lock()
+    .unwrap()
+    .get_widget(&widget_id)
+    .ok_or(WidgetError::NotFound(widget_id))?;
+
+  Ok(widget)
+}
+
+#[tauri::command]
+pub async fn get_widget_appearance<R: Runtime>(
+  app: tauri::AppHandle<R>,
+  widget_id: String,
+) -> Result<HashMap<String, String>, WidgetError> {
+  let widget_handler = app.state::<Mutex<WidgetHandler>>();
+  let widget_handler = widget_handler.lock().unwrap();
+
+  let widget = widget_handler
+    .get_widget(&widget_id)
+    .ok_or(WidgetError::NotFound(widget_id))?;
+
+  Ok(
+    appearance::css_variables(&widget_handler.resolve_appearance(&widget))
+      .into_iter()
+      .map(|(name, value)| (name.to_string(), value))
+      .collect(),
+  )
+}
+
+#[tauri::command]
+pub async fn list_widgets<R: Runtime>(
+  app: tauri::AppHandle<R>,
+) -> Result<Vec<Widget>, WidgetError> {
+  let widget_handler = app.state::<Mutex<WidgetHandler>>();
+
+  let widgets = widget_handler.lock().unwrap().list_widgets();
+
+  Ok(widgets)
+}
+
+#[tauri::command]
+pub async fn create_widget<R: Runtime>(
+  app: tauri::AppHandle<R>,
+  widget: Widget,
+) -> Result<Widget, WidgetError> {
+  let widget_handler = app.state::<Mutex<WidgetHandler>>();
+
+  let widget = widget_handler.lock().unwrap().create_widget(&app, widget)?;
+
+  Ok(widget)
+}
+
+#[tauri::command]
+pub async fn update_widget<R: Runtime>(
+  app: tauri::AppHandle<R>,
+  widget: Widget,
+) -> Result<Widget, WidgetError> {
+  let widget_handler = app.state::<Mutex<WidgetHandler>>();
+
+  let widget = widget_handler.lock().unwrap().update_widget(&app, widget)?;
+
+  Ok(widget)
+}
+
+#[tauri::command]
+pub async fn duplicate_widget<R: Runtime>(
+  app: tauri::AppHandle<R>,
   widget_id: String,
-) -> Result<Widget, String> {
-  let widget_handler = app.state::<WidgetHandler>();
+  new_id: Option<String>,
+) -> Result<Widget, WidgetError> {
+  let widget_handler = app.state::<Mutex<WidgetHandler>>();
 
-  let widget = widget_handler.get_widget(&widget_id).unwrap();
+  let widget = widget_handler
+    .lock()
+    .unwrap()
+    .duplicate_widget(&app, &widget_id, new_id)?;
 
   Ok(widget)
 }
+
+#[tauri::command]
+pub async fn delete_widget<R: Runtime>(
+  app: tauri::AppHandle<R>,
+  widget_id: String,
+) -> Result<(), WidgetError> {
+  let widget_handler = app.state::<Mutex<WidgetHandler>>();
+
+  widget_handler
+    .lock()
+    .unwrap()
+    .delete_widget(&app, &widget_id)?;
+
+  Ok(())
+}
+
+#[tauri::command]
+pub async fn show_widget<R: Runtime>(
+  app: tauri::AppHandle<R>,
+  widget_id: String,
+) -> Result<(), WidgetError> {
+  let widget_handler = app.state::<Mutex<WidgetHandler>>();
+
+  widget_handler
+    .lock()
+    .unwrap()
+    .set_widget_hidden(&app, &widget_id, false)?;
+
+  Ok(())
+}
+
+#[tauri::command]
+pub async fn hide_widget<R: Runtime>(
+  app: tauri::AppHandle<R>,
+  widget_id: String,
+) -> Result<(), WidgetError> {
+  let widget_handler = app.state::<Mutex<WidgetHandler>>();
+
+  widget_handler
+    .lock()
+    .unwrap()
+    .set_widget_hidden(&app, &widget_id, true)?;
+
+  Ok(())
+}
+
+// ========= Profile =========
+
+#[tauri::command]
+pub async fn list_profiles<R: Runtime>(
+  app: tauri::AppHandle<R>,
+) -> Result<ProfileStore, WidgetError> {
+  let widget_handler = app.state::<Mutex<WidgetHandler>>();
+
+  let profiles = widget_handler.lock().unwrap().list_profiles();
+
+  Ok(profiles)
+}
+
+#[tauri::command]
+pub async fn save_profile<R: Runtime>(
+  app: tauri::AppHandle<R>,
+  name: String,
+) -> Result<(), WidgetError> {
+  let widget_handler = app.state::<Mutex<WidgetHandler>>();
+
+  widget_handler.lock().unwrap().save_profile(&app, &name)?;
+
+  Ok(())
+}
+
+#[tauri::command]
+pub async fn switch_profile<R: Runtime>(
+  app: tauri::AppHandle<R>,
+  name: Option<String>,
+) -> Result<(), WidgetError> {
+  let widget_handler = app.state::<Mutex<WidgetHandler>>();
+
+  widget_handler.lock().unwrap().switch_profile(&app, name)?;
+
+  Ok(())
+}
+
+#[tauri::command]
+pub async fn delete_profile<R: Runtime>(
+  app: tauri::AppHandle<R>,
+  name: String,
+) -> Result<(), WidgetError> {
+  let widget_handler = app.state::<Mutex<WidgetHandler>>();
+
+  widget_handler.lock().unwrap().delete_profile(&app, &name)?;
+
+  Ok(())
+}
+
+// ========= Settings =========
+
+#[tauri::command]
+pub async fn get_settings<R: Runtime>(app: tauri::AppHandle<R>) -> Result<Settings, WidgetError> {
+  let widget_handler = app.state::<Mutex<WidgetHandler>>();
+  let settings = widget_handler.lock().unwrap().settings.clone();
+
+  Ok(settings)
+}
+
+#[tauri::command]
+pub async fn set_widgets_locked<R: Runtime>(
+  app: tauri::AppHandle<R>,
+  locked: bool,
+) -> Result<(), WidgetError> {
+  let widget_handler = app.state::<Mutex<WidgetHandler>>();
+
+  widget_handler.lock().unwrap().set_locked(&app, locked)?;
+
+  Ok(())
+}
+
+#[tauri::command]
+pub async fn set_click_through<R: Runtime>(
+  app: tauri::AppHandle<R>,
+  click_through: bool,
+) -> Result<(), WidgetError> {
+  let widget_handler = app.state::<Mutex<WidgetHandler>>();
+
+  widget_handler
+    .lock()
+    .unwrap()
+    .set_click_through(&app, click_through)?;
+
+  Ok(())
+}
+
+// ========= Bundle =========
+
+#[tauri::command]
+pub async fn export_bundle<R: Runtime>(
+  app: tauri::AppHandle<R>,
+  path: String,
+) -> Result<(), WidgetError> {
+  let widget_handler = app.state::<Mutex<WidgetHandler>>();
+
+  widget_handler
+    .lock()
+    .unwrap()
+    .export_bundle(Path::new(&path))?;
+
+  Ok(())
+}
+
+#[tauri::command]
+pub async fn import_bundle<R: Runtime>(
+  app: tauri::AppHandle<R>,
+  path: String,
+  mode: ImportMode,
+) -> Result<Vec<Widget>, WidgetError> {
+  let widget_handler = app.state::<Mutex<WidgetHandler>>();
+
+  let widgets = widget_handler
+    .lock()
+    .unwrap()
+    .import_bundle(&app, Path::new(&path), mode)?;
+
+  Ok(widgets)
+}
+
+// ========= System =========
+
+#[tauri::command]
+pub async fn subscribe_metrics<R: Runtime>(
+  app: tauri::AppHandle<R>,
+  window: tauri::Window<R>,
+  widget_id: String,
+  subscriptions: Vec<MetricSubscription>,
+) -> Result<(), WidgetError> {
+  let widget_handler = app.state::<Mutex<WidgetHandler>>();
+
+  widget_handler.lock().unwrap().subscribe_metrics(
+    &app,
+    &widget_id,
+    window.label(),
+    &subscriptions,
+  )?;
+
+  Ok(())
+}
+
+#[tauri::command]
+pub async fn unsubscribe_metrics<R: Runtime>(
+  app: tauri::AppHandle<R>,
+  widget_id: String,
+) -> Result<(), WidgetError> {
+  let widget_handler = app.state::<Mutex<WidgetHandler>>();
+  widget_handler
+    .lock()
+    .unwrap()
+    .unsubscribe_metrics(&widget_id);
+
+  Ok(())
+}
+
+#[tauri::command]
+pub async fn get_metric_history<R: Runtime>(
+  app: tauri::AppHandle<R>,
+  series: MetricSeries,
+  samples: usize,
+  points: usize,
+  method: Downsample,
+) -> Result<Vec<Point>, WidgetError> {
+  let widget_handler = app.state::<Mutex<WidgetHandler>>();
+
+  let history = widget_handler
+    .lock()
+    .unwrap()
+    .metric_history(series, samples, points, method);
+
+  Ok(history)
+}
+
+#[tauri::command]
+pub async fn get_top_processes<R: Runtime>(
+  app: tauri::AppHandle<R>,
+  sort: ProcessSort,
+  count: usize,
+) -> Result<Vec<ProcessInfo>, WidgetError> {
+  let widget_handler = app.state::<Mutex<WidgetHandler>>();
+  let process_sampler = widget_handler.lock().unwrap().process_sampler();
+
+  let top = process_sampler.lock().unwrap().top(sort, count);
+
+  Ok(top)
+}
+
+// The widget asks the user to confirm before calling this, with the name it
+// showed them.
+#[tauri::command]
+pub async fn end_process(pid: u32, name: String) -> Result<(), WidgetError> {
+  processes::end_process(pid, &name).map_err(WidgetError::Process)
+}
+
+#[tauri::command]
+pub async fn get_batteries() -> Vec<BatteryInfo> {
+  battery::batteries()
+}
+
+// Subscribed widgets get `batteryChanged` events until they unsubscribe or
+// are removed.
+#[tauri::command]
+pub async fn subscribe_battery<R: Runtime>(
+  app: tauri::AppHandle<R>,
+  widget_id: String,
+) -> Result<(), WidgetError> {
+  let widget_handler = app.state::<Mutex<WidgetHandler>>();
+  widget_handler
+    .lock()
+    .unwrap()
+    .subscribe_battery(&widget_id)?;
+
+  Ok(())
+}
+
+#[tauri::command]
+pub async fn unsubscribe_battery<R: Runtime>(
+  app: tauri::AppHandle<R>,
+  widget_id: String,
+) -> Result<(), WidgetError> {
+  let widget_handler = app.state::<Mutex<WidgetHandler>>();
+  widget_handler
+    .lock()
+    .unwrap()
+    .unsubscribe_battery(&widget_id);
+
+  Ok(())
+}
@@ -1,8 +1,7 @@
 mod command;
-mod utils;
+mod registry;
+pub mod utils;
 
-// Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
-use command::greet;
 use utils::widget_handler::WidgetHandler;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -22,8 +21,7 @@ pub fn run() {
       Ok(())
     })
     .plugin(tauri_plugin_shell::init())
-    .invoke_handler(tauri::generate_handler![greet])
-    .invoke_handler(tauri::generate_handler![command::get_widget_config])
+    .invoke_handler(registry::invoke_handler())
     .run(tauri::generate_context!())
     .expect("error while running tauri application");
 }
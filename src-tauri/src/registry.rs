@@ -0,0 +1,233 @@
+// Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
+use tauri::{ipc::Invoke, Runtime};
+
+use crate::command;
+
+// Tauri only keeps the last `invoke_handler` passed to the builder, so every
+// command has to be listed here in a single `generate_handler!`.
+pub fn invoke_handler<R: Runtime>() -> impl Fn(Invoke<R>) -> bool + Send + Sync + 'static {
+  tauri::generate_handler![
+    command::greet,
+    command::get_widget_config,
+    command::get_widget_appearance,
+    command::list_widgets,
+    command::create_widget,
+    command::update_widget,
+    command::duplicate_widget,
+    command::delete_widget,
+    command::show_widget,
+    command::hide_widget,
+    command::list_profiles,
+    command::save_profile,
+    command::switch_profile,
+    command::delete_profile,
+    command::get_settings,
+    command::set_widgets_locked,
+    command::set_click_through,
+    command::export_bundle,
+    command::import_bundle,
+    command::subscribe_metrics,
+    command::unsubscribe_metrics,
+    command::get_metric_history,
+    command::get_top_processes,
+    command::end_process,
+    command::get_batteries,
+    command::subscribe_battery,
+    command::unsubscribe_battery
+  ]
+}
+
+#[cfg(test)]
+mod tests {
+  use std::{fs, path::PathBuf, sync::Mutex};
+
+  use serde_json::{json, Value};
+  use tauri::{
+    ipc::{CallbackFn, InvokeBody},
+    test::{get_ipc_response, mock_builder, mock_context, noop_assets, INVOKE_KEY},
+    webview::InvokeRequest,
+    Manager, WebviewWindow, WebviewWindowBuilder,
+  };
+
+  use super::*;
+  use crate::utils::{config, test_dir::temp_dir, widget_handler::WidgetHandler};
+
+  fn config_dir() -> PathBuf {
+    let dir = temp_dir("registry");
+    fs::create_dir_all(config::widgets_dir(&dir)).unwrap();
+    dir
+  }
+
+  fn invoke(
+    webview: &WebviewWindow<tauri::test::MockRuntime>,
+    cmd: &str,
+    args: Value,
+  ) -> Result<Value, Value> {
+    get_ipc_response(
+      webview,
+      InvokeRequest {
+        cmd: cmd.to_string(),
+        callback: CallbackFn(0),
+        error: CallbackFn(1),
+        url: "http://tauri.localhost".parse().unwrap(),
+        body: InvokeBody::Json(args),
+        headers: Default::default(),
+        invoke_key: INVOKE_KEY.to_string(),
+      },
+    )
+    .map(|body| body.deserialize::<Value>().unwrap())
+  }
+
+  // Every command has to reach its handler and get there with arguments that
+  // deserialize, which come back as a plain string otherwise. None of these
+  // calls open a window, the mock runtime has no monitors to place them on.
+  #[test]
+  fn every_command_is_registered() {
+    let dir = config_dir();
+    let app = mock_builder()
+      .invoke_handler(invoke_handler())
+      .build(mock_context(noop_assets()))
+      .unwrap();
+    app.manage(Mutex::new(WidgetHandler::load(&dir).unwrap()));
+
+    let webview = WebviewWindowBuilder::new(&app, "main", Default::default())
+      .build()
+      .unwrap();
+
+    let bundle = dir.join("bundle.json");
+    let widget = json!({
+      "id": "not a valid id",
+      "description": "",
+      "widget_type": "DefaultDateTime",
+      "property": {},
+      "appearance": {}
+    });
+    // the error kind each call is expected to fail with, `None` when it
+    // succeeds
+    let calls = [
+      ("greet", json!({ "name": "miyabi" }), None),
+      (
+        "get_widget_config",
+        json!({ "widgetId": "missing" }),
+        Some("NotFound"),
+      ),
+      (
+        "get_widget_appearance",
+        json!({ "widgetId": "missing" }),
+        Some("NotFound"),
+      ),
+      ("list_widgets", json!({}), None),
+      (
+        "create_widget",
+        json!({ "widget": widget }),
+        Some("InvalidConfig"),
+      ),
+      (
+        "update_widget",
+        json!({ "widget": widget }),
+        Some("NotFound"),
+      ),
+      (
+        "duplicate_widget",
+        json!({ "widgetId": "missing", "newId": null }),
+        Some("NotFound"),
+      ),
+      (
+        "delete_widget",
+        json!({ "widgetId": "missing" }),
+        Some("NotFound"),
+      ),
+      (
+        "show_widget",
+        json!({ "widgetId": "missing" }),
+        Some("NotFound"),
+      ),
+      (
+        "hide_widget",
+        json!({ "widgetId": "missing" }),
+        Some("NotFound"),
+      ),
+      ("list_profiles", json!({}), None),
+      ("save_profile", json!({ "name": "work" }), None),
+      (
+        "switch_profile",
+        json!({ "name": "missing" }),
+        Some("ProfileNotFound"),
+      ),
+      ("delete_profile", json!({ "name": "work" }), None),
+      ("get_settings", json!({}), None),
+      ("set_widgets_locked", json!({ "locked": true }), None),
+      ("set_click_through", json!({ "clickThrough": false }), None),
+      ("export_bundle", json!({ "path": bundle }), None),
+      (
+        "import_bundle",
+        json!({ "path": dir.join("missing.json"), "mode": "Rename" }),
+        Some("Io"),
+      ),
+      (
+        "subscribe_metrics",
+        json!({ "widgetId": "missing", "subscriptions": [] }),
+        Some("NotFound"),
+      ),
+      (
+        "unsubscribe_metrics",
+        json!({ "widgetId": "missing" }),
+        None,
+      ),
+      (
+        "get_metric_history",
+        json!({ "series": "RamUsed", "samples": 10, "points": 5, "method": "Lttb" }),
+        None,
+      ),
+      (
+        "get_top_processes",
+        json!({ "sort": "Cpu", "count": 5 }),
+        None,
+      ),
+      (
+        "end_process",
+        json!({ "pid": u32::MAX, "name": "missing" }),
+        Some("Process"),
+      ),
+      ("get_batteries", json!({}), None),
+      (
+        "subscribe_battery",
+        json!({ "widgetId": "missing" }),
+        Some("NotFound"),
+      ),
+      (
+        "unsubscribe_battery",
+        json!({ "widgetId": "missing" }),
+        None,
+      ),
+    ];
+
+    // the rejection an unregistered command gets, which none of the calls
+    // above may see
+    assert_eq!(
+      invoke(&webview, "missing_command", json!({})),
+      Err(json!("Command missing_command not found"))
+    );
+
+    for (cmd, args, expected) in calls {
+      let response = invoke(&webview, cmd, args);
+      assert_ne!(
+        response,
+        Err(json!(format!("Command {} not found", cmd))),
+        "{} isn't registered",
+        cmd
+      );
+
+      match expected {
+        None => assert!(response.is_ok(), "{} failed: {:?}", cmd, response),
+        Some(kind) => {
+          let error = response.expect_err(cmd);
+          assert_eq!(error["kind"], json!(kind), "{} failed with {}", cmd, error);
+        }
+      }
+    }
+
+    assert!(bundle.is_file());
+    let _ = fs::remove_dir_all(&dir);
+  }
+}
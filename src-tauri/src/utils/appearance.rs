@@ -0,0 +1,67 @@
+use serde_json::{Map, Value};
+use tauri::{Runtime, WebviewWindow};
+
+use super::widget::{ResolvedAppearance, WidgetTheme};
+
+pub fn css_variables(appearance: &ResolvedAppearance) -> Vec<(&'static str, String)> {
+  let theme = match appearance.theme {
+    WidgetTheme::Normal => "normal",
+    WidgetTheme::Dynamic => "dynamic",
+  };
+
+  vec![
+    ("--widget-theme", theme.to_string()),
+    (
+      "--widget-background-color",
+      appearance.background_color.clone(),
+    ),
+    ("--widget-opacity", appearance.opacity.to_string()),
+    (
+      "--widget-border-size",
+      format!("{}px", appearance.border_size),
+    ),
+    ("--widget-border-color", appearance.border_color.clone()),
+    (
+      "--widget-border-radius",
+      format!("{}px", appearance.border_radius),
+    ),
+    ("--widget-padding", format!("{}px", appearance.padding)),
+    ("--widget-fontscale", appearance.fontscale.to_string()),
+  ]
+}
+
+// The variables are embedded as a JSON object so user supplied values such as
+// colors can't break out of the script.
+pub fn appearance_script(appearance: &ResolvedAppearance) -> String {
+  let variables: Map<String, Value> = css_variables(appearance)
+    .into_iter()
+    .map(|(name, value)| (name.to_string(), Value::String(value)))
+    .collect();
+
+  format!(
+    r#"(function () {{
+  var variables = {};
+  var apply = function () {{
+    var style = document.documentElement.style;
+    for (var name in variables) {{
+      style.setProperty(name, variables[name]);
+    }}
+  }};
+  if (document.documentElement) {{
+    apply();
+  }} else {{
+    document.addEventListener("DOMContentLoaded", apply);
+  }}
+}})();"#,
+    Value::Object(variables)
+  )
+}
+
+pub fn apply_appearance<R: Runtime>(
+  window: &WebviewWindow<R>,
+  appearance: &ResolvedAppearance,
+) -> Result<(), String> {
+  window
+    .eval(&appearance_script(appearance))
+    .map_err(|e| e.to_string())
+}
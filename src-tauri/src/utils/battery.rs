@@ -0,0 +1,320 @@
+use std::{
+  fs,
+  path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+use systemstat::Platform;
+
+use super::sysfs::read_trimmed;
+
+pub const POWER_SUPPLY_ROOT: &str = "/sys/class/power_supply";
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChargingState {
+  Charging,
+  Discharging,
+  Full,
+  // plugged in but held below full, e.g. by a charge limit
+  NotCharging,
+  Unknown,
+}
+
+impl ChargingState {
+  fn from_status(status: &str) -> Self {
+    match status {
+      "Charging" => ChargingState::Charging,
+      "Discharging" => ChargingState::Discharging,
+      "Full" => ChargingState::Full,
+      "Not charging" => ChargingState::NotCharging,
+      _ => ChargingState::Unknown,
+    }
+  }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct BatteryInfo {
+  pub name: String,
+  pub percentage: f32,
+  pub state: ChargingState,
+  // seconds
+  pub time_to_empty: Option<u64>,
+  pub time_to_full: Option<u64>,
+  // full capacity now against the design capacity, in percent
+  pub health: Option<f32>,
+  pub power_draw_watts: Option<f32>,
+}
+
+impl BatteryInfo {
+  // Worth an event: the state changed or the percentage moved by a whole
+  // point. Times and power draw jitter on every read.
+  pub fn changed_from(&self, previous: &BatteryInfo) -> bool {
+    self.state != previous.state || self.percentage.floor() != previous.percentage.floor()
+  }
+}
+
+// Running on battery means at least one battery is discharging. Machines
+// without one never are.
+pub fn on_battery(batteries: &[BatteryInfo]) -> bool {
+  batteries
+    .iter()
+    .any(|battery| battery.state == ChargingState::Discharging)
+}
+
+// Remembers the last readings so an event is only sent when something worth
+// showing changed, or a battery was plugged in or removed.
+#[derive(Debug, Clone, Default)]
+pub struct BatteryTracker {
+  previous: Vec<BatteryInfo>,
+}
+
+impl BatteryTracker {
+  pub fn update(&mut self, batteries: Vec<BatteryInfo>) -> Option<&[BatteryInfo]> {
+    let changed = batteries.len() != self.previous.len()
+      || batteries.iter().any(|battery| {
+        self
+          .previous
+          .iter()
+          .find(|previous| previous.name == battery.name)
+          .is_none_or(|previous| battery.changed_from(previous))
+      });
+
+    self.previous = batteries;
+    changed.then_some(self.previous.as_slice())
+  }
+}
+
+// power_supply values are in micro units (µWh, µAh, µW, µA, µV).
+fn read_micro(path: &Path) -> Option<f64> {
+  read_trimmed(path)?
+    .parse::<f64>()
+    .ok()
+    .map(|value| value / 1_000_000.0)
+}
+
+// Batteries report either energy (Wh) or charge (Ah). Charge is turned into
+// energy with the current voltage so both give the same numbers.
+fn read_energy(battery: &Path, suffix: &str, volts: Option<f64>) -> Option<f64> {
+  read_micro(&battery.join(format!("energy_{}", suffix))).or_else(|| {
+    read_micro(&battery.join(format!("charge_{}", suffix))).and_then(|charge| Some(charge * volts?))
+  })
+}
+
+pub fn read_battery(battery: &Path) -> Option<BatteryInfo> {
+  if read_trimmed(&battery.join("type")).as_deref() != Some("Battery") {
+    return None;
+  }
+  // a removable battery that isn't there still has a directory, and
+  // wireless mice and keyboards report their batteries with a device scope
+  if read_trimmed(&battery.join("present")).as_deref() == Some("0")
+    || read_trimmed(&battery.join("scope")).as_deref() == Some("Device")
+  {
+    return None;
+  }
+
+  let volts = read_micro(&battery.join("voltage_now"));
+  let energy_now = read_energy(battery, "now", volts);
+  let energy_full = read_energy(battery, "full", volts);
+  let energy_full_design = read_energy(battery, "full_design", volts);
+
+  let percentage = read_trimmed(&battery.join("capacity"))
+    .and_then(|capacity| capacity.parse::<f32>().ok())
+    .or_else(|| Some((energy_now? / energy_full? * 100.0) as f32))?;
+
+  let state = read_trimmed(&battery.join("status"))
+    .map(|status| ChargingState::from_status(&status))
+    .unwrap_or(ChargingState::Unknown);
+
+  let watts = read_micro(&battery.join("power_now"))
+    .or_else(|| Some(read_micro(&battery.join("current_now"))? * volts?))
+    .map(f64::abs)
+    .filter(|watts| *watts > 0.0);
+
+  let seconds_until =
+    |energy: Option<f64>| -> Option<u64> { Some((energy? / watts? * 3600.0).round() as u64) };
+
+  Some(BatteryInfo {
+    name: battery
+      .file_name()
+      .map(|name| name.to_string_lossy().to_string())
+      .unwrap_or_default(),
+    percentage: percentage.clamp(0.0, 100.0),
+    state,
+    time_to_empty: match state {
+      ChargingState::Discharging => seconds_until(energy_now),
+      _ => None,
+    },
+    time_to_full: match state {
+      ChargingState::Charging => {
+        seconds_until(energy_full.zip(energy_now).map(|(full, now)| full - now))
+      }
+      _ => None,
+    },
+    health: energy_full
+      .zip(energy_full_design)
+      .filter(|(_, design)| *design > 0.0)
+      .map(|(full, design)| (full / design * 100.0) as f32),
+    power_draw_watts: watts.map(|watts| watts as f32),
+  })
+}
+
+// Every battery under a power_supply root, normally
+// `/sys/class/power_supply`. Chargers and peripherals' batteries are
+// skipped.
+pub fn read_power_supply(root: &Path) -> Vec<BatteryInfo> {
+  let Ok(entries) = fs::read_dir(root) else {
+    return Vec::new();
+  };
+
+  let mut supplies: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).collect();
+  supplies.sort();
+
+  supplies
+    .iter()
+    .filter_map(|supply| read_battery(supply))
+    .collect()
+}
+
+// systemstat only knows the combined charge and remaining time.
+fn read_systemstat() -> Vec<BatteryInfo> {
+  let system = systemstat::System::new();
+  let Ok(life) = system.battery_life() else {
+    return Vec::new();
+  };
+
+  let state = match system.on_ac_power() {
+    Ok(true) if life.remaining_capacity >= 1.0 => ChargingState::Full,
+    Ok(true) => ChargingState::Charging,
+    Ok(false) => ChargingState::Discharging,
+    Err(_) => ChargingState::Unknown,
+  };
+
+  vec![BatteryInfo {
+    name: "Battery".to_string(),
+    percentage: (life.remaining_capacity * 100.0).clamp(0.0, 100.0),
+    state,
+    time_to_empty: (state == ChargingState::Discharging && !life.remaining_time.is_zero())
+      .then_some(life.remaining_time.as_secs()),
+    time_to_full: None,
+    health: None,
+    power_draw_watts: None,
+  }]
+}
+
+pub fn batteries() -> Vec<BatteryInfo> {
+  if cfg!(target_os = "linux") {
+    read_power_supply(Path::new(POWER_SUPPLY_ROOT))
+  } else {
+    read_systemstat()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn fixture() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/power_supply")
+  }
+
+  fn battery(name: &str) -> BatteryInfo {
+    read_power_supply(&fixture())
+      .into_iter()
+      .find(|battery| battery.name == name)
+      .unwrap()
+  }
+
+  #[test]
+  fn read_power_supply_skips_chargers_missing_and_device_batteries() {
+    let names: Vec<String> = read_power_supply(&fixture())
+      .into_iter()
+      .map(|battery| battery.name)
+      .collect();
+
+    // AC is a charger, BAT2 isn't present and the mouse has a device scope
+    assert_eq!(names, ["BAT0", "BAT1", "BAT3"]);
+  }
+
+  #[test]
+  fn read_power_supply_energy_battery() {
+    assert_eq!(
+      battery("BAT0"),
+      BatteryInfo {
+        name: "BAT0".to_string(),
+        percentage: 50.0,
+        state: ChargingState::Discharging,
+        // 20 Wh left at 10 W
+        time_to_empty: Some(7200),
+        time_to_full: None,
+        health: Some(80.0),
+        power_draw_watts: Some(10.0),
+      }
+    );
+  }
+
+  #[test]
+  fn read_power_supply_charge_battery() {
+    let battery = battery("BAT1");
+
+    // no capacity file, 1 of 3 Ah
+    assert_eq!(battery.percentage.round(), 33.0);
+    assert_eq!(battery.state, ChargingState::Charging);
+    // the current is negative while charging, at 10 V
+    assert_eq!(battery.power_draw_watts, Some(20.0));
+    // 20 Wh to go at 20 W
+    assert_eq!(battery.time_to_full, Some(3600));
+    assert_eq!(battery.time_to_empty, None);
+    assert_eq!(battery.health, Some(100.0));
+  }
+
+  #[test]
+  fn read_power_supply_without_energy_readings() {
+    let battery = battery("BAT3");
+
+    assert_eq!(battery.percentage, 80.0);
+    assert_eq!(battery.state, ChargingState::NotCharging);
+    assert_eq!(battery.time_to_empty, None);
+    assert_eq!(battery.time_to_full, None);
+    assert_eq!(battery.health, None);
+    assert_eq!(battery.power_draw_watts, None);
+  }
+
+  #[test]
+  fn read_power_supply_without_root() {
+    assert!(read_power_supply(&fixture().join("missing")).is_empty());
+  }
+
+  #[test]
+  fn on_battery_when_any_discharges() {
+    let batteries = read_power_supply(&fixture());
+
+    assert!(on_battery(&batteries));
+    assert!(!on_battery(&batteries[1..]));
+    assert!(!on_battery(&[]));
+  }
+
+  #[test]
+  fn battery_tracker_reports_worthwhile_changes() {
+    let batteries = read_power_supply(&fixture());
+    let mut tracker = BatteryTracker::default();
+
+    assert!(tracker.update(Vec::new()).is_none());
+    assert_eq!(tracker.update(batteries.clone()).map(<[_]>::len), Some(3));
+    assert!(tracker.update(batteries.clone()).is_none());
+
+    // jitter within the same percent isn't worth an event
+    let mut changed = batteries.clone();
+    changed[0].percentage = 50.7;
+    changed[0].power_draw_watts = Some(3.0);
+    assert!(tracker.update(changed.clone()).is_none());
+
+    changed[0].percentage = 49.9;
+    assert!(tracker.update(changed.clone()).is_some());
+
+    changed[1].state = ChargingState::Full;
+    assert!(tracker.update(changed.clone()).is_some());
+
+    // a battery was removed
+    assert!(tracker.update(changed[..2].to_vec()).is_some());
+  }
+}
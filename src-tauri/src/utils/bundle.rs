@@ -0,0 +1,251 @@
+use std::{
+  collections::BTreeMap,
+  path::{Component, Path, PathBuf},
+};
+
+use base64::{engine::general_purpose, Engine as _};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::widget::{unique_widget_id, Widget};
+
+pub const BUNDLE_VERSION: u32 = 1;
+
+// Widgets are kept as raw JSON so a bundle goes through the same migrations
+// as the files in the widgets directory when it is imported.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Bundle {
+  pub version: u32,
+  pub widgets: Vec<Value>,
+  pub theme: Option<Value>,
+  // base64 encoded file contents by path relative to the config dir
+  pub assets: BTreeMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportMode {
+  // imported widgets whose id is taken get a fresh one, and so do assets
+  // whose path holds a different file
+  Rename,
+  // imported widgets replace the existing ones with the same id
+  Overwrite,
+}
+
+// Before bundles had a version they were a plain array of widget configs.
+fn migrate_v0(raw: Value) -> Result<Value, String> {
+  match raw {
+    Value::Array(widgets) => Ok(json!({
+      "version": 1,
+      "widgets": widgets,
+      "theme": null,
+      "assets": {},
+    })),
+    _ => Err("unversioned bundle is not an array of widgets".to_string()),
+  }
+}
+
+// `MIGRATIONS[n]` upgrades a bundle from version `n` to `n + 1`.
+const MIGRATIONS: [fn(Value) -> Result<Value, String>; BUNDLE_VERSION as usize] = [migrate_v0];
+
+fn bundle_version(raw: &Value) -> Result<u32, String> {
+  match raw.get("version") {
+    None => Ok(0),
+    Some(version) => version
+      .as_u64()
+      .map(|version| version as u32)
+      .ok_or_else(|| format!("invalid bundle version: {}", version)),
+  }
+}
+
+pub fn migrate_bundle(mut raw: Value) -> Result<Bundle, String> {
+  let version = bundle_version(&raw)?;
+  if version > BUNDLE_VERSION {
+    return Err(format!(
+      "bundle version {} is newer than the supported version {}",
+      version, BUNDLE_VERSION
+    ));
+  }
+
+  for migration in &MIGRATIONS[version as usize..] {
+    raw = migration(raw)?;
+  }
+
+  serde_json::from_value(raw).map_err(|e| format!("failed to parse bundle: {}", e))
+}
+
+fn collect_icons(widgets: &[Widget], icons: &mut Vec<String>) {
+  for widget in widgets {
+    if let Some(icon) = &widget.property.icon {
+      if !icons.contains(icon) {
+        icons.push(icon.clone());
+      }
+    }
+    collect_icons(widget.children.as_deref().unwrap_or_default(), icons);
+  }
+}
+
+// Icons referenced by the widgets that live inside the config dir. Absolute
+// paths point somewhere else on this machine and are left out.
+pub fn asset_paths(widgets: &[Widget]) -> Vec<String> {
+  let mut icons = Vec::new();
+  collect_icons(widgets, &mut icons);
+
+  icons
+    .into_iter()
+    .filter(|icon| asset_path(icon).is_ok())
+    .collect()
+}
+
+// Asset paths come from the bundle, so only plain relative paths are allowed
+// to keep an import from writing outside the config dir.
+pub fn asset_path(path: &str) -> Result<PathBuf, String> {
+  let path = Path::new(path);
+
+  if path.as_os_str().is_empty()
+    || !path
+      .components()
+      .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+  {
+    return Err(format!("invalid asset path: {}", path.display()));
+  }
+
+  Ok(path.to_path_buf())
+}
+
+pub fn encode_asset(contents: &[u8]) -> String {
+  general_purpose::STANDARD.encode(contents)
+}
+
+pub fn decode_asset(encoded: &str) -> Result<Vec<u8>, String> {
+  general_purpose::STANDARD
+    .decode(encoded)
+    .map_err(|e| format!("failed to decode asset: {}", e))
+}
+
+// Renames every widget in the tree whose id is already taken, registering
+// each id so the imported widgets don't collide with each other either.
+pub fn rename_colliding_ids(widget: &mut Widget, taken: &mut Vec<String>) {
+  if taken.contains(&widget.id) {
+    widget.id = unique_widget_id(&widget.id, taken);
+  }
+  taken.push(widget.id.clone());
+
+  for child in widget.children.iter_mut().flatten() {
+    rename_colliding_ids(child, taken);
+  }
+}
+
+// A free path next to an asset whose path is taken: `icons/clock.png`
+// becomes `icons/clock-copy.png`, then `icons/clock-copy-2.png`, like
+// renamed widget ids.
+pub fn unique_asset_path(asset: &str, taken: impl Fn(&str) -> bool) -> String {
+  let name_start = asset.rfind('/').map_or(0, |slash| slash + 1);
+  let (stem, extension) = match asset[name_start..].rfind('.') {
+    Some(dot) if dot > 0 => asset.split_at(name_start + dot),
+    _ => (asset, ""),
+  };
+
+  std::iter::once("-copy".to_string())
+    .chain((2..).map(|n| format!("-copy-{}", n)))
+    .map(|suffix| format!("{}{}{}", stem, suffix, extension))
+    .find(|candidate| !taken(candidate))
+    .unwrap()
+}
+
+// Points the icons in the tree at the paths their assets were renamed to.
+pub fn rename_icons(widget: &mut Widget, renamed: &BTreeMap<String, String>) {
+  if let Some(icon) = widget.property.icon.as_mut() {
+    if let Some(new_path) = renamed.get(icon) {
+      *icon = new_path.clone();
+    }
+  }
+
+  for child in widget.children.iter_mut().flatten() {
+    rename_icons(child, renamed);
+  }
+}
+
+// Palettes from the bundle are added next to the existing ones; on a name
+// clash and for the defaults the existing theme wins.
+pub fn merge_theme(existing: &mut Value, imported: Value) {
+  let (Some(existing), Value::Object(imported)) = (existing.as_object_mut(), imported) else {
+    return;
+  };
+
+  for (key, value) in imported {
+    match (key.as_str(), existing.get_mut(&key)) {
+      ("palettes", Some(Value::Object(palettes))) => {
+        if let Value::Object(imported_palettes) = value {
+          for (name, palette) in imported_palettes {
+            palettes.entry(name).or_insert(palette);
+          }
+        }
+      }
+      (_, Some(_)) => {}
+      (_, None) => {
+        existing.insert(key, value);
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use serde_json::json;
+
+  use super::*;
+
+  #[test]
+  fn unique_asset_path_keeps_extension() {
+    let taken = ["icons/clock-copy.png"];
+
+    assert_eq!(
+      unique_asset_path("icons/clock.png", |_| false),
+      "icons/clock-copy.png"
+    );
+    assert_eq!(
+      unique_asset_path("icons/clock.png", |path| taken.contains(&path)),
+      "icons/clock-copy-2.png"
+    );
+  }
+
+  #[test]
+  fn unique_asset_path_without_extension() {
+    assert_eq!(
+      unique_asset_path("icons.d/clock", |_| false),
+      "icons.d/clock-copy"
+    );
+    assert_eq!(unique_asset_path(".icon", |_| false), ".icon-copy");
+  }
+
+  #[test]
+  fn rename_icons_follows_children() {
+    let mut widget: Widget = serde_json::from_value(json!({
+      "id": "group",
+      "description": "",
+      "widget_type": "Container",
+      "property": { "icon": "icons/group.png" },
+      "appearance": {},
+      "children": [{
+        "id": "clock",
+        "description": "",
+        "widget_type": "DefaultDateTime",
+        "property": { "icon": "icons/clock.png" },
+        "appearance": {},
+      }],
+    }))
+    .unwrap();
+    let renamed = BTreeMap::from([(
+      "icons/clock.png".to_string(),
+      "icons/clock-copy.png".to_string(),
+    )]);
+
+    rename_icons(&mut widget, &renamed);
+
+    assert_eq!(widget.property.icon.as_deref(), Some("icons/group.png"));
+    assert_eq!(
+      widget.children.unwrap()[0].property.icon.as_deref(),
+      Some("icons/clock-copy.png")
+    );
+  }
+}
@@ -0,0 +1,258 @@
+use std::{
+  fs,
+  io::Write,
+  path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+use serde_json::{json, ser::PrettyFormatter, Serializer, Value};
+
+use super::{
+  profile::ProfileStore,
+  settings::Settings,
+  widget::{ThemeConfig, WidgetPlacement},
+};
+
+pub fn widgets_dir(config_dir: &Path) -> PathBuf {
+  config_dir.join("widgets")
+}
+
+pub fn theme_path(config_dir: &Path) -> PathBuf {
+  config_dir.join("theme.json")
+}
+
+// A missing theme file just means there are no global defaults or palettes.
+pub fn load_theme(config_dir: &Path) -> Result<ThemeConfig, String> {
+  let path = theme_path(config_dir);
+
+  if !path.exists() {
+    return Ok(ThemeConfig::default());
+  }
+
+  serde_json::from_value(read_json(&path)?)
+    .map_err(|e| format!("failed to parse {}: {}", path.display(), e))
+}
+
+pub fn profiles_path(config_dir: &Path) -> PathBuf {
+  config_dir.join("profiles.json")
+}
+
+pub fn load_profiles(config_dir: &Path) -> Result<ProfileStore, String> {
+  let path = profiles_path(config_dir);
+
+  if !path.exists() {
+    return Ok(ProfileStore::default());
+  }
+
+  serde_json::from_value(read_json(&path)?)
+    .map_err(|e| format!("failed to parse {}: {}", path.display(), e))
+}
+
+pub fn save_profiles(config_dir: &Path, profiles: &ProfileStore) -> Result<(), String> {
+  let raw =
+    serde_json::to_value(profiles).map_err(|e| format!("failed to serialize profiles: {}", e))?;
+
+  write_json_preserving_format(&profiles_path(config_dir), &raw)
+}
+
+pub fn settings_path(config_dir: &Path) -> PathBuf {
+  config_dir.join("settings.json")
+}
+
+pub fn load_settings(config_dir: &Path) -> Result<Settings, String> {
+  let path = settings_path(config_dir);
+
+  if !path.exists() {
+    return Ok(Settings::default());
+  }
+
+  serde_json::from_value(read_json(&path)?)
+    .map_err(|e| format!("failed to parse {}: {}", path.display(), e))
+}
+
+pub fn save_settings(config_dir: &Path, settings: &Settings) -> Result<(), String> {
+  let raw =
+    serde_json::to_value(settings).map_err(|e| format!("failed to serialize settings: {}", e))?;
+
+  write_json_preserving_format(&settings_path(config_dir), &raw)
+}
+
+// Guess the indentation of an existing JSON file from its first indented line,
+// falling back to two spaces when the file is minified or empty.
+pub fn detect_indent(raw: &str) -> String {
+  for line in raw.lines().skip(1) {
+    let indent: String = line
+      .chars()
+      .take_while(|c| *c == ' ' || *c == '\t')
+      .collect();
+
+    if !indent.is_empty() {
+      return indent;
+    }
+  }
+
+  "  ".to_string()
+}
+
+pub fn to_json_string(value: &Value, indent: &str) -> Result<String, String> {
+  let mut buf = Vec::new();
+  let formatter = PrettyFormatter::with_indent(indent.as_bytes());
+  let mut serializer = Serializer::with_formatter(&mut buf, formatter);
+
+  value
+    .serialize(&mut serializer)
+    .map_err(|e| format!("failed to serialize json: {}", e))?;
+
+  String::from_utf8(buf).map_err(|e| format!("failed to encode json: {}", e))
+}
+
+pub fn atomic_write(path: &Path, contents: &[u8]) -> Result<(), String> {
+  let tmp_path = path.with_extension("tmp");
+
+  let mut file = fs::File::create(&tmp_path)
+    .map_err(|e| format!("failed to create {}: {}", tmp_path.display(), e))?;
+  file
+    .write_all(contents)
+    .and_then(|_| file.sync_all())
+    .map_err(|e| format!("failed to write {}: {}", tmp_path.display(), e))?;
+
+  fs::rename(&tmp_path, path).map_err(|e| {
+    let _ = fs::remove_file(&tmp_path);
+    format!("failed to replace {}: {}", path.display(), e)
+  })
+}
+
+// Rewrite a JSON file with the same indentation and trailing newline it had
+// before. Key order is kept because serde_json is built with `preserve_order`.
+pub fn write_json_preserving_format(path: &Path, value: &Value) -> Result<(), String> {
+  let original = fs::read_to_string(path).unwrap_or_default();
+  let mut contents = to_json_string(value, &detect_indent(&original))?;
+
+  if original.is_empty() || original.ends_with('\n') {
+    contents.push('\n');
+  }
+
+  atomic_write(path, contents.as_bytes())
+}
+
+// Copies a file next to itself before it is rewritten, e.g. `clock.json` to
+// `clock.json.v0.bak`. An existing backup is never replaced.
+pub fn backup_file(path: &Path, suffix: &str) -> Result<PathBuf, String> {
+  let mut backup = path.as_os_str().to_owned();
+  backup.push(format!(".{}.bak", suffix));
+  let backup = PathBuf::from(backup);
+
+  if !backup.exists() {
+    fs::copy(path, &backup).map_err(|e| format!("failed to back up {}: {}", path.display(), e))?;
+  }
+
+  Ok(backup)
+}
+
+pub fn read_json(path: &Path) -> Result<Value, String> {
+  let raw =
+    fs::read_to_string(path).map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+
+  serde_json::from_str(&raw).map_err(|e| format!("failed to parse {}: {}", path.display(), e))
+}
+
+pub fn update_widget_property(path: &Path, key: &str, value: Value) -> Result<(), String> {
+  let mut raw = read_json(path)?;
+
+  raw
+    .as_object_mut()
+    .ok_or_else(|| "widget config is not a json object".to_string())?
+    .entry("property")
+    .or_insert_with(|| json!({}))
+    .as_object_mut()
+    .ok_or_else(|| "widget property is not a json object".to_string())?
+    .insert(key.to_string(), value);
+
+  write_json_preserving_format(path, &raw)
+}
+
+pub fn set_widget_geometry(
+  raw: &mut Value,
+  position: Option<(i32, i32)>,
+  placement: Option<&WidgetPlacement>,
+  size: Option<(u32, u32)>,
+) -> Result<(), String> {
+  let root = raw
+    .as_object_mut()
+    .ok_or_else(|| "widget config is not a json object".to_string())?;
+
+  let property = root
+    .entry("property")
+    .or_insert_with(|| json!({}))
+    .as_object_mut()
+    .ok_or_else(|| "widget property is not a json object".to_string())?;
+
+  if let Some((x, y)) = position {
+    property.insert("position".to_string(), json!([x, y]));
+  }
+
+  if let Some(placement) = placement {
+    let placement = serde_json::to_value(placement)
+      .map_err(|e| format!("failed to serialize placement: {}", e))?;
+    property.insert("placement".to_string(), placement);
+  }
+
+  if let Some((width, height)) = size {
+    property.insert("size".to_string(), json!([width, height]));
+  }
+
+  Ok(())
+}
+
+pub fn update_widget_geometry(
+  path: &Path,
+  position: Option<(i32, i32)>,
+  placement: Option<&WidgetPlacement>,
+  size: Option<(u32, u32)>,
+) -> Result<(), String> {
+  let mut raw = read_json(path)?;
+
+  set_widget_geometry(&mut raw, position, placement, size)?;
+
+  write_json_preserving_format(path, &raw)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::utils::test_dir::temp_dir;
+
+  #[test]
+  fn backup_file_copies_next_to_original() {
+    let dir = temp_dir("backup");
+    let path = dir.join("clock.json");
+    fs::write(&path, "{}").unwrap();
+
+    let backup = backup_file(&path, "v0").unwrap();
+
+    assert_eq!(backup, dir.join("clock.json.v0.bak"));
+    assert_eq!(fs::read_to_string(&backup).unwrap(), "{}");
+    let _ = fs::remove_dir_all(&dir);
+  }
+
+  #[test]
+  fn backup_file_keeps_existing_backup() {
+    let dir = temp_dir("backup-existing");
+    let path = dir.join("clock.json");
+    fs::write(&path, "{\"new\": true}").unwrap();
+    fs::write(dir.join("clock.json.v0.bak"), "{}").unwrap();
+
+    let backup = backup_file(&path, "v0").unwrap();
+
+    assert_eq!(fs::read_to_string(&backup).unwrap(), "{}");
+    let _ = fs::remove_dir_all(&dir);
+  }
+
+  #[test]
+  fn backup_file_of_missing_file() {
+    let dir = temp_dir("backup-missing");
+
+    assert!(backup_file(&dir.join("clock.json"), "v0").is_err());
+    let _ = fs::remove_dir_all(&dir);
+  }
+}
@@ -0,0 +1,53 @@
+use windows::Win32::{
+  Foundation::{HWND, RECT},
+  Graphics::Gdi::{GetMonitorInfoW, MonitorFromWindow, MONITORINFO, MONITOR_DEFAULTTONEAREST},
+  UI::WindowsAndMessaging::{
+    GetClassNameW, GetForegroundWindow, GetShellWindow, GetWindowRect, GetWindowThreadProcessId,
+  },
+};
+
+// The desktop covers its whole monitor as well, so it never counts as a
+// fullscreen app.
+const DESKTOP_CLASSES: [&str; 2] = ["Progman", "WorkerW"];
+
+pub fn fullscreen_app_focused() -> bool {
+  unsafe {
+    let hwnd = GetForegroundWindow();
+    if hwnd == HWND::default() || hwnd == GetShellWindow() {
+      return false;
+    }
+
+    // our own widgets are never what the rules react to
+    let mut process_id = 0;
+    GetWindowThreadProcessId(hwnd, Some(&mut process_id as *mut u32));
+    if process_id == std::process::id() {
+      return false;
+    }
+
+    let mut class_name = [0u16; 256];
+    let len = GetClassNameW(hwnd, &mut class_name).max(0) as usize;
+    if DESKTOP_CLASSES.contains(&String::from_utf16_lossy(&class_name[..len]).as_str()) {
+      return false;
+    }
+
+    let mut window = RECT::default();
+    if GetWindowRect(hwnd, &mut window).is_err() {
+      return false;
+    }
+
+    let mut monitor = MONITORINFO {
+      cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+      ..Default::default()
+    };
+    let hmonitor = MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST);
+    if !GetMonitorInfoW(hmonitor, &mut monitor).as_bool() {
+      return false;
+    }
+
+    let screen = monitor.rcMonitor;
+    window.left <= screen.left
+      && window.top <= screen.top
+      && window.right >= screen.right
+      && window.bottom >= screen.bottom
+  }
+}
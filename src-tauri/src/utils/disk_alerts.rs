@@ -0,0 +1,68 @@
+use std::collections::HashSet;
+
+use serde::Serialize;
+
+use super::{settings::DiskAlertSettings, system::DiskInfo};
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct DiskAlert {
+  pub mount_point: String,
+  pub available_space: u64,
+  pub total_space: u64,
+  // false once the disk has enough free space again
+  pub low: bool,
+}
+
+pub fn is_low(disk: &DiskInfo, settings: &DiskAlertSettings) -> bool {
+  if disk.total_space == 0 {
+    return false;
+  }
+
+  let free_percent = disk.available_space as f64 / disk.total_space as f64 * 100.0;
+
+  settings
+    .min_free_percent
+    .is_some_and(|min| free_percent < min)
+    || settings
+      .min_free_bytes
+      .is_some_and(|min| disk.available_space < min)
+}
+
+// Remembers which disks are low so an alert is only sent when a disk crosses
+// the threshold, not on every check.
+#[derive(Debug, Clone, Default)]
+pub struct DiskAlerts {
+  low: HashSet<String>,
+}
+
+impl DiskAlerts {
+  pub fn update(&mut self, disks: &[DiskInfo], settings: &DiskAlertSettings) -> Vec<DiskAlert> {
+    let mut alerts = Vec::new();
+
+    for disk in disks
+      .iter()
+      .filter(|disk| settings.watches(&disk.mount_point))
+    {
+      let low = is_low(disk, settings);
+      let was_low = self.low.contains(&disk.mount_point);
+      if low == was_low {
+        continue;
+      }
+
+      if low {
+        self.low.insert(disk.mount_point.clone());
+      } else {
+        self.low.remove(&disk.mount_point);
+      }
+
+      alerts.push(DiskAlert {
+        mount_point: disk.mount_point.clone(),
+        available_space: disk.available_space,
+        total_space: disk.total_space,
+        low,
+      });
+    }
+
+    alerts
+  }
+}
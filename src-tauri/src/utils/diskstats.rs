@@ -0,0 +1,311 @@
+use std::{collections::HashMap, time::Instant};
+
+use serde::Serialize;
+
+use super::rate::{per_second, Clock, SystemClock};
+
+pub const DISKSTATS_PATH: &str = "/proc/diskstats";
+
+// diskstats counts in 512 byte sectors whatever the device's sector size is.
+const SECTOR_SIZE: u64 = 512;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiskStat {
+  pub name: String,
+  pub reads: u64,
+  pub sectors_read: u64,
+  pub writes: u64,
+  pub sectors_written: u64,
+}
+
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub struct DiskIo {
+  // the physical device, e.g. `sda` or `nvme0n1`
+  pub device: String,
+  pub partitions: Vec<String>,
+  pub read_bytes_per_sec: f64,
+  pub written_bytes_per_sec: f64,
+  pub reads_per_sec: f64,
+  pub writes_per_sec: f64,
+}
+
+// One line per device:
+//   major minor name reads reads_merged sectors_read ms_reading writes
+//   writes_merged sectors_written ...
+// Newer kernels append more fields, lines that are too short are skipped.
+pub fn parse_diskstats(contents: &str) -> Vec<DiskStat> {
+  contents
+    .lines()
+    .filter_map(|line| {
+      let fields: Vec<&str> = line.split_whitespace().collect();
+      if fields.len() < 10 {
+        return None;
+      }
+
+      let number = |index: usize| fields[index].parse::<u64>().ok();
+      Some(DiskStat {
+        name: fields[2].to_string(),
+        reads: number(3)?,
+        sectors_read: number(5)?,
+        writes: number(7)?,
+        sectors_written: number(9)?,
+      })
+    })
+    .collect()
+}
+
+// Loop devices, ram disks and compressed swap aren't disks, and device
+// mapper and software raid volumes sit on top of disks that are already
+// counted.
+pub fn is_virtual_device(name: &str) -> bool {
+  ["loop", "ram", "zram", "dm-", "md"]
+    .iter()
+    .any(|prefix| name.starts_with(prefix))
+}
+
+// `sda1` -> `sda`, `nvme0n1p2` -> `nvme0n1`, `mmcblk0p1` -> `mmcblk0`.
+// Whole disks and anything unknown map to themselves.
+pub fn physical_device(name: &str) -> String {
+  let trimmed = name.trim_end_matches(|c: char| c.is_ascii_digit());
+  if trimmed.len() == name.len() || trimmed.is_empty() {
+    return name.to_string();
+  }
+
+  // devices whose own name ends in a number put a `p` before the partition
+  if let Some(device) = trimmed.strip_suffix('p') {
+    if device.ends_with(|c: char| c.is_ascii_digit()) {
+      return device.to_string();
+    }
+  }
+
+  if name.starts_with("sd")
+    || name.starts_with("hd")
+    || name.starts_with("vd")
+    || name.starts_with("xvd")
+  {
+    return trimmed.to_string();
+  }
+
+  name.to_string()
+}
+
+// Throughput per physical device from the difference between two reads of
+// diskstats. The whole disk line already includes its partitions, so only
+// those lines are used for the rates.
+#[derive(Debug, Clone, Default)]
+pub struct DiskIoTracker<C: Clock = SystemClock> {
+  clock: C,
+  previous: HashMap<String, (Instant, DiskStat)>,
+  io: Vec<DiskIo>,
+}
+
+impl<C: Clock> DiskIoTracker<C> {
+  pub fn new(clock: C) -> Self {
+    Self {
+      clock,
+      previous: HashMap::new(),
+      io: Vec::new(),
+    }
+  }
+
+  pub fn update(&mut self, stats: Vec<DiskStat>) {
+    let now = self.clock.now();
+
+    let mut partitions: HashMap<String, Vec<String>> = HashMap::new();
+    for stat in &stats {
+      let device = physical_device(&stat.name);
+      if device != stat.name {
+        partitions
+          .entry(device)
+          .or_default()
+          .push(stat.name.clone());
+      }
+    }
+
+    let disks: Vec<DiskStat> = stats
+      .into_iter()
+      .filter(|stat| !is_virtual_device(&stat.name) && physical_device(&stat.name) == stat.name)
+      .collect();
+
+    self.io = disks
+      .iter()
+      .map(|stat| {
+        let mut io = DiskIo {
+          device: stat.name.clone(),
+          partitions: partitions.remove(&stat.name).unwrap_or_default(),
+          ..DiskIo::default()
+        };
+
+        if let Some((at, previous)) = self.previous.get(&stat.name) {
+          let elapsed = now.saturating_duration_since(*at);
+          io.read_bytes_per_sec = per_second(
+            previous.sectors_read * SECTOR_SIZE,
+            stat.sectors_read * SECTOR_SIZE,
+            elapsed,
+          );
+          io.written_bytes_per_sec = per_second(
+            previous.sectors_written * SECTOR_SIZE,
+            stat.sectors_written * SECTOR_SIZE,
+            elapsed,
+          );
+          io.reads_per_sec = per_second(previous.reads, stat.reads, elapsed);
+          io.writes_per_sec = per_second(previous.writes, stat.writes, elapsed);
+        }
+
+        io
+      })
+      .collect();
+
+    self.previous = disks
+      .into_iter()
+      .map(|stat| (stat.name.clone(), (now, stat)))
+      .collect();
+  }
+
+  pub fn io(&self) -> &[DiskIo] {
+    &self.io
+  }
+
+  // Bytes per second read and written over every disk.
+  pub fn total(&self) -> (f64, f64) {
+    self.io.iter().fold((0.0, 0.0), |(read, written), io| {
+      (
+        read + io.read_bytes_per_sec,
+        written + io.written_bytes_per_sec,
+      )
+    })
+  }
+}
+
+pub fn read_diskstats() -> Option<Vec<DiskStat>> {
+  if !cfg!(target_os = "linux") {
+    return None;
+  }
+
+  std::fs::read_to_string(DISKSTATS_PATH)
+    .map(|contents| parse_diskstats(&contents))
+    .map_err(|e| eprintln!("Failed to read {}: {}", DISKSTATS_PATH, e))
+    .ok()
+}
+
+#[cfg(test)]
+mod tests {
+  use std::time::Duration;
+
+  use super::*;
+  use crate::utils::rate::FakeClock;
+
+  const FIXTURE: &str = include_str!("../../tests/fixtures/diskstats");
+
+  fn tracker() -> (DiskIoTracker<FakeClock>, FakeClock) {
+    let clock = FakeClock::default();
+    (DiskIoTracker::new(clock.clone()), clock)
+  }
+
+  fn devices(tracker: &DiskIoTracker<FakeClock>) -> Vec<&str> {
+    tracker.io().iter().map(|io| io.device.as_str()).collect()
+  }
+
+  #[test]
+  fn parse_diskstats_reads_fields() {
+    let stats = parse_diskstats(FIXTURE);
+
+    assert_eq!(
+      stats[0],
+      DiskStat {
+        name: "sda".to_string(),
+        reads: 1000,
+        sectors_read: 20000,
+        writes: 2000,
+        sectors_written: 40000,
+      }
+    );
+    // older kernels stop after 14 fields
+    assert_eq!(stats[3].name, "nvme0n1");
+    assert_eq!(stats[3].sectors_written, 2000);
+  }
+
+  #[test]
+  fn parse_diskstats_skips_short_and_broken_lines() {
+    let stats = parse_diskstats(FIXTURE);
+
+    assert_eq!(stats.len(), 12);
+    assert!(!stats.iter().any(|stat| stat.name == "dm-1"));
+    assert!(parse_diskstats("\n8 0 sda x 0 0 0 0 0 0\n8 0 sda 1 2 3 4 5 6").is_empty());
+  }
+
+  #[test]
+  fn physical_device_of_partitions() {
+    for (name, device) in [
+      ("sda", "sda"),
+      ("sda1", "sda"),
+      ("sdb12", "sdb"),
+      ("vda2", "vda"),
+      ("xvda3", "xvda"),
+      ("nvme0n1", "nvme0n1"),
+      ("nvme0n1p2", "nvme0n1"),
+      ("mmcblk0", "mmcblk0"),
+      ("mmcblk0p1", "mmcblk0"),
+      ("sr0", "sr0"),
+      ("dm-0", "dm-0"),
+      ("md0", "md0"),
+    ] {
+      assert_eq!(physical_device(name), device, "{}", name);
+    }
+  }
+
+  #[test]
+  fn is_virtual_device_names() {
+    for name in ["loop0", "ram0", "zram0", "dm-0", "md127"] {
+      assert!(is_virtual_device(name), "{}", name);
+    }
+    for name in ["sda", "nvme0n1", "mmcblk0", "sr0"] {
+      assert!(!is_virtual_device(name), "{}", name);
+    }
+  }
+
+  #[test]
+  fn disk_io_tracker_groups_partitions_under_disks() {
+    let (mut tracker, _) = tracker();
+    tracker.update(parse_diskstats(FIXTURE));
+
+    assert_eq!(devices(&tracker), ["sda", "nvme0n1", "mmcblk0"]);
+    assert_eq!(tracker.io()[0].partitions, ["sda1", "sda2"]);
+    assert_eq!(tracker.io()[1].partitions, ["nvme0n1p1"]);
+    assert_eq!(tracker.io()[2].partitions, ["mmcblk0p1"]);
+    // nothing to compare against yet
+    assert_eq!(tracker.total(), (0.0, 0.0));
+  }
+
+  #[test]
+  fn disk_io_tracker_rates_from_whole_disks() {
+    let (mut tracker, clock) = tracker();
+    tracker.update(parse_diskstats(FIXTURE));
+    clock.advance(Duration::from_secs(2));
+    tracker.update(parse_diskstats(&FIXTURE.replace(
+      "sda 1000 10 20000 500 2000 20 40000",
+      "sda 1100 10 24000 500 2200 20 42000",
+    )));
+
+    let sda = &tracker.io()[0];
+    assert_eq!(sda.read_bytes_per_sec, 4000.0 * 512.0 / 2.0);
+    assert_eq!(sda.written_bytes_per_sec, 2000.0 * 512.0 / 2.0);
+    assert_eq!(sda.reads_per_sec, 50.0);
+    assert_eq!(sda.writes_per_sec, 100.0);
+    // the partitions didn't change, and aren't counted twice anyway
+    assert_eq!(tracker.total(), (1_024_000.0, 512_000.0));
+  }
+
+  #[test]
+  fn disk_io_tracker_skips_counter_reset() {
+    let (mut tracker, clock) = tracker();
+    tracker.update(parse_diskstats(FIXTURE));
+    clock.advance(Duration::from_secs(1));
+    tracker.update(parse_diskstats(
+      &FIXTURE.replace("sda 1000 10 20000", "sda 10 10 200"),
+    ));
+
+    assert_eq!(tracker.io()[0].read_bytes_per_sec, 0.0);
+    assert_eq!(tracker.io()[0].reads_per_sec, 0.0);
+  }
+}
@@ -0,0 +1,37 @@
+use std::fmt;
+
+// Serialized as `{ "kind": "NotFound", "message": "..." }` so the control
+// panel can branch on the kind instead of parsing messages.
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(tag = "kind", content = "message")]
+pub enum WidgetError {
+  NotFound(String),
+  ProfileNotFound(String),
+  AlreadyExists(String),
+  InvalidConfig(String),
+  Io(String),
+  Window(String),
+  Process(String),
+}
+
+impl fmt::Display for WidgetError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      WidgetError::NotFound(id) => write!(f, "widget not found: {}", id),
+      WidgetError::ProfileNotFound(name) => write!(f, "profile not found: {}", name),
+      WidgetError::AlreadyExists(id) => write!(f, "widget already exists: {}", id),
+      WidgetError::InvalidConfig(message) => write!(f, "invalid widget config: {}", message),
+      WidgetError::Io(message) => write!(f, "failed to access widget config: {}", message),
+      WidgetError::Window(message) => write!(f, "widget window error: {}", message),
+      WidgetError::Process(message) => write!(f, "process error: {}", message),
+    }
+  }
+}
+
+impl std::error::Error for WidgetError {}
+
+impl From<tauri::Error> for WidgetError {
+  fn from(e: tauri::Error) -> Self {
+    WidgetError::Window(e.to_string())
+  }
+}
@@ -0,0 +1,292 @@
+use std::{
+  fs,
+  panic::{self, AssertUnwindSafe},
+  path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+
+pub const DRM_ROOT: &str = "/sys/class/drm";
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct GpuInfo {
+  pub id: String,
+  // percentages, the ones a source can't read are left empty
+  pub memory_usage: Option<u32>,
+  pub encoder_usage: Option<u32>,
+  pub decoder_usage: Option<u32>,
+  pub global_utilization: u32,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GpuSupport {
+  Supported,
+  // no source found a GPU it can read, so there is nothing to show
+  Unsupported,
+}
+
+pub trait GpuSource {
+  fn usage(&mut self) -> Vec<GpuInfo>;
+}
+
+// machine_info unwraps NVML results, so a driver hiccup panics inside the
+// crate. That is treated like a source finding nothing instead of taking the
+// fetcher thread down with it.
+fn catch_panic<T>(read: impl FnOnce() -> T) -> Option<T> {
+  panic::catch_unwind(AssertUnwindSafe(read)).ok()
+}
+
+// NVIDIA GPUs through NVML.
+pub struct NvidiaSource {
+  machine: machine_info::Machine,
+}
+
+impl NvidiaSource {
+  // Empty when NVML isn't installed or finds no GPU.
+  pub fn probe() -> Option<Self> {
+    let machine = catch_panic(machine_info::Machine::new)?;
+    if catch_panic(|| machine.graphics_status())
+      .unwrap_or_default()
+      .is_empty()
+    {
+      return None;
+    }
+
+    Some(Self { machine })
+  }
+}
+
+impl GpuSource for NvidiaSource {
+  fn usage(&mut self) -> Vec<GpuInfo> {
+    catch_panic(|| self.machine.graphics_status())
+      .unwrap_or_default()
+      .into_iter()
+      .map(|gpu| GpuInfo {
+        id: gpu.id,
+        memory_usage: Some(gpu.memory_usage),
+        encoder_usage: Some(gpu.encoder),
+        decoder_usage: Some(gpu.decoder),
+        global_utilization: gpu.gpu,
+      })
+      .collect()
+  }
+}
+
+fn read_percent(path: &Path) -> Option<u32> {
+  fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+// AMD GPUs through the amdgpu driver's `gpu_busy_percent` under a drm root,
+// normally `/sys/class/drm`.
+pub struct AmdSysfsSource {
+  // card name and its device directory
+  cards: Vec<(String, PathBuf)>,
+}
+
+impl AmdSysfsSource {
+  pub fn probe(root: &Path) -> Option<Self> {
+    let entries = fs::read_dir(root).ok()?;
+
+    let mut cards: Vec<(String, PathBuf)> = entries
+      .flatten()
+      .filter_map(|entry| {
+        let name = entry.file_name().into_string().ok()?;
+        // connectors show up as `card0-DP-1` next to their card
+        name.strip_prefix("card")?.parse::<u32>().ok()?;
+
+        let device = entry.path().join("device");
+        device
+          .join("gpu_busy_percent")
+          .exists()
+          .then_some((name, device))
+      })
+      .collect();
+    cards.sort();
+
+    if cards.is_empty() {
+      return None;
+    }
+
+    Some(Self { cards })
+  }
+}
+
+impl GpuSource for AmdSysfsSource {
+  fn usage(&mut self) -> Vec<GpuInfo> {
+    self
+      .cards
+      .iter()
+      .filter_map(|(name, device)| {
+        Some(GpuInfo {
+          id: name.clone(),
+          global_utilization: read_percent(&device.join("gpu_busy_percent"))?,
+          memory_usage: read_percent(&device.join("mem_busy_percent")),
+          encoder_usage: None,
+          decoder_usage: None,
+        })
+      })
+      .collect()
+  }
+}
+
+// Finds the sources that can read a GPU on this machine. Probing loads NVML
+// and walks sysfs, so it is done once and the result kept.
+pub fn probe_sources() -> Vec<Box<dyn GpuSource>> {
+  let mut sources: Vec<Box<dyn GpuSource>> = Vec::new();
+
+  if let Some(nvidia) = NvidiaSource::probe() {
+    sources.push(Box::new(nvidia));
+  }
+
+  if cfg!(target_os = "linux") {
+    if let Some(amd) = AmdSysfsSource::probe(Path::new(DRM_ROOT)) {
+      sources.push(Box::new(amd));
+    }
+  }
+
+  sources
+}
+
+pub fn read_sources(sources: &mut [Box<dyn GpuSource>]) -> Vec<GpuInfo> {
+  sources
+    .iter_mut()
+    .flat_map(|source| catch_panic(|| source.usage()).unwrap_or_default())
+    .collect()
+}
+
+pub fn support(sources: &[Box<dyn GpuSource>]) -> GpuSupport {
+  if sources.is_empty() {
+    GpuSupport::Unsupported
+  } else {
+    GpuSupport::Supported
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::utils::test_dir::{temp_dir, write};
+
+  struct FakeSource(Vec<GpuInfo>);
+
+  impl GpuSource for FakeSource {
+    fn usage(&mut self) -> Vec<GpuInfo> {
+      self.0.clone()
+    }
+  }
+
+  struct PanickingSource;
+
+  impl GpuSource for PanickingSource {
+    fn usage(&mut self) -> Vec<GpuInfo> {
+      panic!("NVML call failed");
+    }
+  }
+
+  fn gpu(id: &str, global_utilization: u32) -> GpuInfo {
+    GpuInfo {
+      id: id.to_string(),
+      memory_usage: None,
+      encoder_usage: None,
+      decoder_usage: None,
+      global_utilization,
+    }
+  }
+
+  #[test]
+  fn amd_sysfs_source_reads_cards() {
+    let root = temp_dir("drm-cards");
+    write(&root.join("card1/device"), "gpu_busy_percent", "12\n");
+    write(&root.join("card0/device"), "gpu_busy_percent", "37\n");
+    write(&root.join("card0/device"), "mem_busy_percent", "5\n");
+
+    let mut source = AmdSysfsSource::probe(&root).unwrap();
+
+    assert_eq!(
+      source.usage(),
+      [
+        GpuInfo {
+          memory_usage: Some(5),
+          ..gpu("card0", 37)
+        },
+        gpu("card1", 12),
+      ]
+    );
+    let _ = fs::remove_dir_all(&root);
+  }
+
+  #[test]
+  fn amd_sysfs_source_skips_connectors_and_other_drivers() {
+    let root = temp_dir("drm-connectors");
+    write(&root.join("card0/device"), "gpu_busy_percent", "37");
+    // a connector has a device directory of its own
+    write(&root.join("card0-DP-1/device"), "gpu_busy_percent", "99");
+    // not amdgpu, e.g. i915
+    write(&root.join("card1/device"), "vendor", "0x8086");
+    write(&root.join("renderD128/device"), "gpu_busy_percent", "99");
+
+    let mut source = AmdSysfsSource::probe(&root).unwrap();
+
+    assert_eq!(source.usage(), [gpu("card0", 37)]);
+    let _ = fs::remove_dir_all(&root);
+  }
+
+  #[test]
+  fn amd_sysfs_source_skips_unreadable_cards() {
+    let root = temp_dir("drm-unreadable");
+    write(&root.join("card0/device"), "gpu_busy_percent", "");
+    write(&root.join("card1/device"), "gpu_busy_percent", "20");
+
+    let mut source = AmdSysfsSource::probe(&root).unwrap();
+
+    assert_eq!(source.usage(), [gpu("card1", 20)]);
+    let _ = fs::remove_dir_all(&root);
+  }
+
+  #[test]
+  fn amd_sysfs_source_without_cards() {
+    let root = temp_dir("drm-empty");
+    write(&root.join("card0-HDMI-A-1"), "status", "connected");
+
+    assert!(AmdSysfsSource::probe(&root).is_none());
+    assert!(AmdSysfsSource::probe(&root.join("missing")).is_none());
+    let _ = fs::remove_dir_all(&root);
+  }
+
+  #[test]
+  fn read_sources_joins_every_source() {
+    let mut sources: Vec<Box<dyn GpuSource>> = vec![
+      Box::new(FakeSource(vec![gpu("nvidia0", 50)])),
+      Box::new(FakeSource(vec![gpu("card0", 10), gpu("card1", 20)])),
+    ];
+
+    let ids: Vec<String> = read_sources(&mut sources)
+      .into_iter()
+      .map(|gpu| gpu.id)
+      .collect();
+
+    assert_eq!(ids, ["nvidia0", "card0", "card1"]);
+  }
+
+  #[test]
+  fn read_sources_survives_empty_and_panicking_sources() {
+    let mut sources: Vec<Box<dyn GpuSource>> = vec![
+      Box::new(PanickingSource),
+      Box::new(FakeSource(Vec::new())),
+      Box::new(FakeSource(vec![gpu("card0", 10)])),
+    ];
+
+    assert_eq!(read_sources(&mut sources), [gpu("card0", 10)]);
+    // the panicking source is asked again on the next sample
+    assert_eq!(read_sources(&mut sources), [gpu("card0", 10)]);
+  }
+
+  #[test]
+  fn support_without_sources() {
+    assert_eq!(support(&[]), GpuSupport::Unsupported);
+    assert_eq!(
+      support(&[Box::new(FakeSource(Vec::new())) as Box<dyn GpuSource>]),
+      GpuSupport::Supported
+    );
+  }
+}
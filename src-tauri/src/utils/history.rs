@@ -0,0 +1,321 @@
+use std::collections::{HashMap, VecDeque};
+
+use serde::{Deserialize, Serialize};
+
+// 10 minutes of samples at the default one second interval.
+pub const HISTORY_CAPACITY: usize = 600;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MetricSeries {
+  // usage of one core in percent
+  Cpu(usize),
+  // bytes
+  RamUsed,
+  // bytes per second, summed over all interfaces
+  NetworkReceived,
+  NetworkTransmitted,
+  // bytes per second, summed over all disks, linux only
+  DiskRead,
+  DiskWritten,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Point {
+  // milliseconds since the unix epoch
+  pub time: u64,
+  pub value: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Downsample {
+  Min,
+  Max,
+  Avg,
+  // largest triangle three buckets, keeps the shape of the line
+  Lttb,
+}
+
+// Keeps the last `capacity` samples, dropping the oldest one on overflow.
+#[derive(Debug, Clone)]
+pub struct RingBuffer<T> {
+  samples: VecDeque<T>,
+  capacity: usize,
+}
+
+impl<T: Clone> RingBuffer<T> {
+  pub fn new(capacity: usize) -> Self {
+    Self {
+      samples: VecDeque::with_capacity(capacity),
+      capacity,
+    }
+  }
+
+  pub fn push(&mut self, sample: T) {
+    if self.capacity == 0 {
+      return;
+    }
+    if self.samples.len() == self.capacity {
+      self.samples.pop_front();
+    }
+    self.samples.push_back(sample);
+  }
+
+  pub fn len(&self) -> usize {
+    self.samples.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.samples.is_empty()
+  }
+
+  // The newest `count` samples, oldest first.
+  pub fn last(&self, count: usize) -> Vec<T> {
+    let skip = self.samples.len().saturating_sub(count);
+    self.samples.iter().skip(skip).cloned().collect()
+  }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MetricHistory {
+  series: HashMap<MetricSeries, RingBuffer<Point>>,
+}
+
+impl MetricHistory {
+  pub fn record(&mut self, series: MetricSeries, point: Point) {
+    self
+      .series
+      .entry(series)
+      .or_insert_with(|| RingBuffer::new(HISTORY_CAPACITY))
+      .push(point);
+  }
+
+  pub fn last(&self, series: MetricSeries, count: usize) -> Vec<Point> {
+    self
+      .series
+      .get(&series)
+      .map(|buffer| buffer.last(count))
+      .unwrap_or_default()
+  }
+}
+
+// Splits `len` items into `buckets` contiguous ranges whose sizes differ by
+// at most one.
+fn bucket_ranges(len: usize, buckets: usize) -> impl Iterator<Item = std::ops::Range<usize>> {
+  (0..buckets).map(move |bucket| bucket * len / buckets..(bucket + 1) * len / buckets)
+}
+
+fn aggregate(points: &[Point], target: usize, method: Downsample) -> Vec<Point> {
+  bucket_ranges(points.len(), target)
+    .map(|range| {
+      let bucket = &points[range];
+      match method {
+        Downsample::Min => *bucket
+          .iter()
+          .min_by(|a, b| a.value.total_cmp(&b.value))
+          .unwrap(),
+        Downsample::Max => *bucket
+          .iter()
+          .max_by(|a, b| a.value.total_cmp(&b.value))
+          .unwrap(),
+        _ => {
+          let len = bucket.len() as f64;
+          Point {
+            time: (bucket.iter().map(|point| point.time as f64).sum::<f64>() / len) as u64,
+            value: bucket.iter().map(|point| point.value).sum::<f64>() / len,
+          }
+        }
+      }
+    })
+    .collect()
+}
+
+fn triangle_area(a: Point, b: Point, c: (f64, f64)) -> f64 {
+  let (ax, bx) = (a.time as f64, b.time as f64);
+  ((ax - c.0) * (b.value - a.value) - (ax - bx) * (c.1 - a.value)).abs() / 2.0
+}
+
+// Keeps the first and last point and, from each bucket in between, the point
+// forming the largest triangle with the previously kept point and the
+// average of the next bucket.
+fn lttb(points: &[Point], target: usize) -> Vec<Point> {
+  if target < 3 {
+    return aggregate(points, target, Downsample::Avg);
+  }
+
+  let inner = &points[1..points.len() - 1];
+  let ranges: Vec<_> = bucket_ranges(inner.len(), target - 2).collect();
+
+  let mut sampled = Vec::with_capacity(target);
+  sampled.push(points[0]);
+
+  for (index, range) in ranges.iter().enumerate() {
+    let next_average = match ranges.get(index + 1) {
+      Some(next) => {
+        let next = &inner[next.clone()];
+        let len = next.len() as f64;
+        (
+          next.iter().map(|point| point.time as f64).sum::<f64>() / len,
+          next.iter().map(|point| point.value).sum::<f64>() / len,
+        )
+      }
+      None => {
+        let last = points[points.len() - 1];
+        (last.time as f64, last.value)
+      }
+    };
+
+    let previous = *sampled.last().unwrap();
+    let chosen = inner[range.clone()]
+      .iter()
+      .max_by(|a, b| {
+        triangle_area(previous, **a, next_average).total_cmp(&triangle_area(
+          previous,
+          **b,
+          next_average,
+        ))
+      })
+      .unwrap();
+    sampled.push(*chosen);
+  }
+
+  sampled.push(points[points.len() - 1]);
+  sampled
+}
+
+// Reduces `points` to at most `target` points. Nothing is dropped when there
+// are already few enough.
+pub fn downsample(points: &[Point], target: usize, method: Downsample) -> Vec<Point> {
+  if target == 0 || points.len() <= target {
+    return points.to_vec();
+  }
+
+  match method {
+    Downsample::Lttb => lttb(points, target),
+    _ => aggregate(points, target, method),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // one point per second
+  fn points(values: &[f64]) -> Vec<Point> {
+    values
+      .iter()
+      .enumerate()
+      .map(|(index, value)| Point {
+        time: index as u64 * 1000,
+        value: *value,
+      })
+      .collect()
+  }
+
+  fn values(points: &[Point]) -> Vec<f64> {
+    points.iter().map(|point| point.value).collect()
+  }
+
+  #[test]
+  fn ring_buffer_drops_oldest_on_overflow() {
+    let mut buffer = RingBuffer::new(3);
+    for sample in 0..5 {
+      buffer.push(sample);
+    }
+
+    assert_eq!(buffer.len(), 3);
+    assert_eq!(buffer.last(10), [2, 3, 4]);
+    assert_eq!(buffer.last(2), [3, 4]);
+  }
+
+  #[test]
+  fn ring_buffer_without_capacity_stays_empty() {
+    let mut buffer = RingBuffer::new(0);
+    buffer.push(1);
+
+    assert!(buffer.is_empty());
+    assert!(buffer.last(1).is_empty());
+  }
+
+  #[test]
+  fn metric_history_keeps_series_apart() {
+    let mut history = MetricHistory::default();
+    for point in points(&[1.0, 2.0]) {
+      history.record(MetricSeries::Cpu(0), point);
+    }
+    history.record(MetricSeries::Cpu(1), points(&[9.0])[0]);
+
+    assert_eq!(values(&history.last(MetricSeries::Cpu(0), 10)), [1.0, 2.0]);
+    assert_eq!(values(&history.last(MetricSeries::Cpu(1), 10)), [9.0]);
+    assert!(history.last(MetricSeries::RamUsed, 10).is_empty());
+  }
+
+  #[test]
+  fn downsample_min_max_per_bucket() {
+    let line = points(&[1.0, 5.0, 2.0, 8.0, 3.0, 0.0]);
+
+    let min = downsample(&line, 3, Downsample::Min);
+    assert_eq!(values(&min), [1.0, 2.0, 0.0]);
+    // the point itself is kept, with its time
+    assert_eq!(min[2], line[5]);
+
+    assert_eq!(
+      values(&downsample(&line, 3, Downsample::Max)),
+      [5.0, 8.0, 3.0]
+    );
+  }
+
+  #[test]
+  fn downsample_avg_averages_time_and_value() {
+    let averaged = downsample(&points(&[1.0, 5.0, 2.0, 8.0, 3.0, 0.0]), 3, Downsample::Avg);
+
+    assert_eq!(values(&averaged), [3.0, 5.0, 1.5]);
+    assert_eq!(averaged[0].time, 500);
+    assert_eq!(averaged[2].time, 4500);
+  }
+
+  #[test]
+  fn downsample_uneven_buckets() {
+    // 7 points in 3 buckets of 2, 2 and 3
+    let line = points(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0]);
+
+    assert_eq!(
+      values(&downsample(&line, 3, Downsample::Min)),
+      [1.0, 3.0, 5.0]
+    );
+    assert_eq!(
+      values(&downsample(&line, 3, Downsample::Max)),
+      [2.0, 4.0, 7.0]
+    );
+  }
+
+  #[test]
+  fn downsample_keeps_short_series() {
+    let line = points(&[1.0, 2.0, 3.0]);
+
+    assert_eq!(downsample(&line, 3, Downsample::Min), line);
+    assert_eq!(downsample(&line, 10, Downsample::Lttb), line);
+    assert_eq!(downsample(&line, 0, Downsample::Avg), line);
+  }
+
+  #[test]
+  fn lttb_keeps_first_last_and_peaks() {
+    let line = points(&[0.0, 0.0, 0.0, 10.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0]);
+    let sampled = downsample(&line, 4, Downsample::Lttb);
+
+    assert_eq!(sampled.len(), 4);
+    assert_eq!(sampled[0], line[0]);
+    assert_eq!(sampled[3], line[9]);
+    assert_eq!(sampled[1], line[3]);
+  }
+
+  #[test]
+  fn lttb_with_target_below_three_averages() {
+    let line = points(&[0.0, 2.0, 4.0, 6.0]);
+
+    assert_eq!(
+      downsample(&line, 2, Downsample::Lttb),
+      downsample(&line, 2, Downsample::Avg)
+    );
+    assert_eq!(values(&downsample(&line, 1, Downsample::Lttb)), [3.0]);
+  }
+}
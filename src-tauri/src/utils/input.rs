@@ -0,0 +1,32 @@
+use windows::Win32::UI::Input::KeyboardAndMouse::{
+  GetAsyncKeyState, VIRTUAL_KEY, VK_CONTROL, VK_LBUTTON, VK_LWIN, VK_MENU, VK_RBUTTON, VK_RWIN,
+  VK_SHIFT,
+};
+
+use super::settings::ModifierKey;
+
+fn any_held(keys: &[VIRTUAL_KEY]) -> bool {
+  keys
+    .iter()
+    .any(|key| unsafe { GetAsyncKeyState(key.0 as i32) } as u16 & 0x8000 != 0)
+}
+
+// Click-through windows never receive key events, so the modifier state is
+// read from the OS directly.
+pub fn modifier_held(modifier: ModifierKey) -> bool {
+  let keys: &[VIRTUAL_KEY] = match modifier {
+    ModifierKey::Alt => &[VK_MENU],
+    ModifierKey::Ctrl => &[VK_CONTROL],
+    ModifierKey::Shift => &[VK_SHIFT],
+    ModifierKey::Super => &[VK_LWIN, VK_RWIN],
+  };
+
+  any_held(keys)
+}
+
+// A drag or resize by the user happens with a mouse button held, moves made
+// by the app or by the OS (e.g. off a removed monitor) don't. Both buttons
+// are checked since they can be swapped.
+pub fn mouse_button_held() -> bool {
+  any_held(&[VK_LBUTTON, VK_RBUTTON])
+}
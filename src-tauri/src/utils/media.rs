@@ -1,25 +1,16 @@
 use std::fmt;
-use std::ops::Deref;
 
 use base64::{engine::general_purpose, Engine as _};
 use image::{DynamicImage, ImageBuffer, RgbaImage};
 use std::io::Cursor;
-use tauri::{async_runtime, App, Emitter, Listener};
-use windows::Foundation::TypedEventHandler;
-use windows::Media::Control::{
-  GlobalSystemMediaTransportControlsSessionPlaybackStatus as WinPlaybackStatus,
-  MediaPropertiesChangedEventArgs, PlaybackInfoChangedEventArgs,
-  TimelinePropertiesChangedEventArgs,
-};
+use tauri::{async_runtime, AppHandle, Emitter, Listener, Runtime};
+use windows::Media::Control::GlobalSystemMediaTransportControlsSessionPlaybackStatus as WinPlaybackStatus;
 use windows::{
   Graphics::Imaging::BitmapDecoder,
-  Media::{
-    Control::{
-      GlobalSystemMediaTransportControlsSession, GlobalSystemMediaTransportControlsSessionManager,
-      GlobalSystemMediaTransportControlsSessionMediaProperties,
-      GlobalSystemMediaTransportControlsSessionTimelineProperties,
-    },
-    Playback::MediaPlayer,
+  Media::Control::{
+    GlobalSystemMediaTransportControlsSession, GlobalSystemMediaTransportControlsSessionManager,
+    GlobalSystemMediaTransportControlsSessionMediaProperties,
+    GlobalSystemMediaTransportControlsSessionTimelineProperties,
   },
 };
 
@@ -362,9 +353,9 @@ pub struct MediaControlCommand {
   pub command: String,
 }
 
-pub fn initiate_media_control(app: &App) -> Result<(), String> {
-  let app_handle = app.handle().clone();
-  let app_handle2 = app.handle().clone();
+pub fn initiate_media_control<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+  let app_handle = app.clone();
+  let app_handle2 = app.clone();
 
   async_runtime::spawn(async move {
     loop {
@@ -0,0 +1,185 @@
+use serde_json::{json, Map, Value};
+
+pub const SCHEMA_VERSION: u32 = 1;
+
+fn for_each_widget(raw: &mut Value, f: &mut impl FnMut(&mut Map<String, Value>)) {
+  let Some(widget) = raw.as_object_mut() else {
+    return;
+  };
+  f(widget);
+
+  if let Some(Value::Array(children)) = widget.get_mut("children") {
+    for child in children {
+      for_each_widget(child, f);
+    }
+  }
+}
+
+// `Bottom` was the first name of the desktop layer.
+fn migrate_v0(raw: &mut Value) -> Result<(), String> {
+  for_each_widget(raw, &mut |widget| {
+    if let Some(Value::Object(property)) = widget.get_mut("property") {
+      if property.get("layer") == Some(&json!("Bottom")) {
+        property.insert("layer".to_string(), json!("Desktop"));
+      }
+    }
+  });
+
+  Ok(())
+}
+
+type Migration = fn(&mut Value) -> Result<(), String>;
+
+// `MIGRATIONS[n]` upgrades a widget config from version `n` to `n + 1`.
+const MIGRATIONS: [Migration; SCHEMA_VERSION as usize] = [migrate_v0];
+
+// Configs written before the field existed are version 0.
+pub fn schema_version(raw: &Value) -> Result<u32, String> {
+  match raw.get("schema_version") {
+    None | Some(Value::Null) => Ok(0),
+    Some(version) => version
+      .as_u64()
+      .map(|version| version as u32)
+      .ok_or_else(|| format!("invalid schema_version: {}", version)),
+  }
+}
+
+pub fn set_schema_version(raw: &mut Value) -> Result<(), String> {
+  let widget = raw
+    .as_object_mut()
+    .ok_or_else(|| "widget config is not a json object".to_string())?;
+
+  if widget.contains_key("schema_version") {
+    widget.insert("schema_version".to_string(), json!(SCHEMA_VERSION));
+  } else {
+    // a new field goes first so it's the first thing seen in the file
+    let mut stamped = Map::new();
+    stamped.insert("schema_version".to_string(), json!(SCHEMA_VERSION));
+    stamped.extend(std::mem::take(widget));
+    *widget = stamped;
+  }
+
+  Ok(())
+}
+
+// Upgrades a raw widget config in place to the current schema and returns the
+// version it had before.
+pub fn migrate_widget(raw: &mut Value) -> Result<u32, String> {
+  let version = schema_version(raw)?;
+  if version > SCHEMA_VERSION {
+    return Err(format!(
+      "schema_version {} is newer than the supported version {}",
+      version, SCHEMA_VERSION
+    ));
+  }
+
+  for migration in &MIGRATIONS[version as usize..] {
+    migration(raw)?;
+  }
+  set_schema_version(raw)?;
+
+  Ok(version)
+}
+
+#[cfg(test)]
+mod tests {
+  use serde_json::json;
+
+  use super::*;
+  use crate::utils::widget::{Widget, WidgetLayer};
+
+  fn fixture(name: &str) -> Value {
+    let raw = match name {
+      "clock" => include_str!("../../tests/fixtures/v0/clock.json"),
+      "container" => include_str!("../../tests/fixtures/v0/container.json"),
+      _ => unreachable!(),
+    };
+    serde_json::from_str(raw).unwrap()
+  }
+
+  #[test]
+  fn migrate_widget_upgrades_v0_fixtures() {
+    for name in ["clock", "container"] {
+      let mut raw = fixture(name);
+
+      assert_eq!(migrate_widget(&mut raw), Ok(0), "{}", name);
+      assert_eq!(
+        raw.as_object().unwrap().keys().next().map(String::as_str),
+        Some("schema_version")
+      );
+      assert_eq!(raw["schema_version"], json!(SCHEMA_VERSION));
+
+      let widget: Widget = serde_json::from_value(raw).unwrap();
+      assert_eq!(
+        widget.property.layer,
+        Some(WidgetLayer::Desktop),
+        "{}",
+        name
+      );
+    }
+  }
+
+  #[test]
+  fn migrate_widget_renames_bottom_layer_of_children() {
+    let mut raw = fixture("container");
+    migrate_widget(&mut raw).unwrap();
+
+    assert_eq!(raw["children"][0]["property"]["layer"], json!("Desktop"));
+    // other layers are left alone
+    assert_eq!(raw["children"][1]["property"]["layer"], json!("Top"));
+  }
+
+  #[test]
+  fn migrate_widget_keeps_current_config() {
+    let mut raw = fixture("clock");
+    migrate_widget(&mut raw).unwrap();
+    let migrated = raw.clone();
+
+    assert_eq!(migrate_widget(&mut raw), Ok(SCHEMA_VERSION));
+    assert_eq!(raw, migrated);
+  }
+
+  #[test]
+  fn migrate_widget_rejects_newer_version() {
+    let mut raw = json!({ "schema_version": SCHEMA_VERSION + 1 });
+
+    assert_eq!(
+      migrate_widget(&mut raw),
+      Err(format!(
+        "schema_version {} is newer than the supported version {}",
+        SCHEMA_VERSION + 1,
+        SCHEMA_VERSION
+      ))
+    );
+  }
+
+  #[test]
+  fn schema_version_of_null_and_invalid_values() {
+    assert_eq!(schema_version(&json!({ "schema_version": null })), Ok(0));
+    assert!(schema_version(&json!({ "schema_version": "1" })).is_err());
+  }
+
+  #[test]
+  fn set_schema_version_goes_first_when_missing() {
+    let mut raw = json!({ "id": "clock", "property": {} });
+    set_schema_version(&mut raw).unwrap();
+
+    let keys: Vec<&String> = raw.as_object().unwrap().keys().collect();
+    assert_eq!(keys, ["schema_version", "id", "property"]);
+  }
+
+  #[test]
+  fn set_schema_version_keeps_existing_position() {
+    let mut raw = json!({ "id": "clock", "schema_version": 0, "property": {} });
+    set_schema_version(&mut raw).unwrap();
+
+    let keys: Vec<&String> = raw.as_object().unwrap().keys().collect();
+    assert_eq!(keys, ["id", "schema_version", "property"]);
+    assert_eq!(raw["schema_version"], json!(SCHEMA_VERSION));
+  }
+
+  #[test]
+  fn set_schema_version_rejects_non_objects() {
+    assert!(set_schema_version(&mut json!([])).is_err());
+  }
+}
@@ -1,5 +1,31 @@
+pub mod appearance;
+pub mod battery;
+pub mod bundle;
+pub mod config;
+pub mod desktop;
+pub mod disk_alerts;
+pub mod diskstats;
+pub mod error;
+pub mod gpu;
+pub mod history;
+pub mod input;
 pub mod media;
+pub mod migration;
+pub mod network;
+pub mod placement;
+pub mod processes;
+pub mod profile;
+pub mod rate;
+pub mod rules;
+pub mod sensors;
+pub mod settings;
+pub mod snap;
+pub mod subscription;
+pub mod sysfs;
 pub mod system;
+#[cfg(test)]
+pub mod test_dir;
+pub mod tray;
 pub mod widget;
 pub mod widget_handler;
 pub mod app_launcher;
@@ -0,0 +1,319 @@
+use std::{
+  collections::HashMap,
+  time::{Duration, Instant},
+};
+
+use serde::Serialize;
+
+use super::{
+  rate::{per_second, Clock, SystemClock},
+  settings::NetworkSettings,
+};
+
+// Totals since boot, as the OS reports them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NetworkCounters {
+  pub received: u64,
+  pub transmitted: u64,
+  pub packets_received: u64,
+  pub packets_transmitted: u64,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct NetworkRates {
+  pub received_per_sec: f64,
+  pub transmitted_per_sec: f64,
+  pub packets_received_per_sec: f64,
+  pub packets_transmitted_per_sec: f64,
+}
+
+impl NetworkRates {
+  pub fn add(&self, other: &NetworkRates) -> NetworkRates {
+    NetworkRates {
+      received_per_sec: self.received_per_sec + other.received_per_sec,
+      transmitted_per_sec: self.transmitted_per_sec + other.transmitted_per_sec,
+      packets_received_per_sec: self.packets_received_per_sec + other.packets_received_per_sec,
+      packets_transmitted_per_sec: self.packets_transmitted_per_sec
+        + other.packets_transmitted_per_sec,
+    }
+  }
+}
+
+pub fn rates(
+  previous: &NetworkCounters,
+  current: &NetworkCounters,
+  elapsed: Duration,
+) -> NetworkRates {
+  NetworkRates {
+    received_per_sec: per_second(previous.received, current.received, elapsed),
+    transmitted_per_sec: per_second(previous.transmitted, current.transmitted, elapsed),
+    packets_received_per_sec: per_second(
+      previous.packets_received,
+      current.packets_received,
+      elapsed,
+    ),
+    packets_transmitted_per_sec: per_second(
+      previous.packets_transmitted,
+      current.packets_transmitted,
+      elapsed,
+    ),
+  }
+}
+
+// Rates come from the difference of the totals over the time that really
+// passed between two updates, so they don't depend on when the counters were
+// refreshed.
+#[derive(Debug, Clone, Default)]
+pub struct RateTracker<C: Clock = SystemClock> {
+  clock: C,
+  previous: HashMap<String, (Instant, NetworkCounters)>,
+  rates: HashMap<String, NetworkRates>,
+}
+
+impl<C: Clock> RateTracker<C> {
+  pub fn new(clock: C) -> Self {
+    Self {
+      clock,
+      previous: HashMap::new(),
+      rates: HashMap::new(),
+    }
+  }
+
+  // Interfaces seen for the first time have a rate of zero until the next
+  // update, and interfaces that disappeared are forgotten.
+  pub fn update(&mut self, counters: HashMap<String, NetworkCounters>) {
+    let now = self.clock.now();
+
+    self.rates = counters
+      .iter()
+      .map(|(interface, current)| {
+        let rates = match self.previous.get(interface) {
+          Some((at, previous)) => rates(previous, current, now.saturating_duration_since(*at)),
+          None => NetworkRates::default(),
+        };
+        (interface.clone(), rates)
+      })
+      .collect();
+
+    self.previous = counters
+      .into_iter()
+      .map(|(interface, current)| (interface, (now, current)))
+      .collect();
+  }
+
+  pub fn rates(&self) -> &HashMap<String, NetworkRates> {
+    &self.rates
+  }
+
+  pub fn total(&self) -> NetworkRates {
+    self
+      .rates
+      .values()
+      .fold(NetworkRates::default(), |total, rates| total.add(rates))
+  }
+}
+
+pub fn is_loopback(interface: &str) -> bool {
+  let interface = interface.to_lowercase();
+  interface == "lo" || interface.starts_with("lo0") || interface.contains("loopback")
+}
+
+// Bridges, tunnels and the adapters container and VM software creates.
+// Linux names them with short prefixes, Windows reports adapter aliases like
+// "VMware Network Adapter VMnet8" or "vEthernet (WSL)".
+pub fn is_virtual(interface: &str) -> bool {
+  const PREFIXES: [&str; 10] = [
+    "docker", "veth", "br-", "virbr", "vmnet", "vboxnet", "tun", "tap", "utun", "zt",
+  ];
+  const ALIASES: [&str; 10] = [
+    "vmware",
+    "virtualbox",
+    "hyper-v",
+    "vethernet",
+    "wsl",
+    "tap-windows",
+    "wintun",
+    "zerotier",
+    "tailscale",
+    "openvpn",
+  ];
+
+  let interface = interface.to_lowercase();
+  PREFIXES.iter().any(|prefix| interface.starts_with(prefix))
+    || ALIASES.iter().any(|alias| interface.contains(alias))
+}
+
+pub fn include_interface(interface: &str, settings: &NetworkSettings) -> bool {
+  if settings
+    .exclude
+    .iter()
+    .flatten()
+    .any(|excluded| excluded == interface)
+  {
+    return false;
+  }
+
+  (settings.include_loopback() || !is_loopback(interface))
+    && (settings.include_virtual() || !is_virtual(interface))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::utils::rate::FakeClock;
+
+  fn tracker() -> (RateTracker<FakeClock>, FakeClock) {
+    let clock = FakeClock::default();
+    (RateTracker::new(clock.clone()), clock)
+  }
+
+  fn counters(interfaces: &[(&str, u64)]) -> HashMap<String, NetworkCounters> {
+    interfaces
+      .iter()
+      .map(|(interface, received)| {
+        (
+          interface.to_string(),
+          NetworkCounters {
+            received: *received,
+            transmitted: received / 2,
+            packets_received: received / 100,
+            packets_transmitted: 0,
+          },
+        )
+      })
+      .collect()
+  }
+
+  #[test]
+  fn rate_tracker_starts_at_zero() {
+    let (mut tracker, _) = tracker();
+    tracker.update(counters(&[("eth0", 1000)]));
+
+    assert_eq!(tracker.rates()["eth0"], NetworkRates::default());
+  }
+
+  #[test]
+  fn rate_tracker_divides_by_elapsed_time() {
+    let (mut tracker, clock) = tracker();
+    tracker.update(counters(&[("eth0", 1000)]));
+    clock.advance(Duration::from_millis(500));
+    tracker.update(counters(&[("eth0", 2000)]));
+
+    assert_eq!(
+      tracker.rates()["eth0"],
+      NetworkRates {
+        received_per_sec: 2000.0,
+        transmitted_per_sec: 1000.0,
+        packets_received_per_sec: 20.0,
+        packets_transmitted_per_sec: 0.0,
+      }
+    );
+  }
+
+  #[test]
+  fn rate_tracker_skips_counter_reset() {
+    let (mut tracker, clock) = tracker();
+    tracker.update(counters(&[("eth0", 5000)]));
+    clock.advance(Duration::from_secs(1));
+    tracker.update(counters(&[("eth0", 100)]));
+
+    assert_eq!(tracker.rates()["eth0"].received_per_sec, 0.0);
+
+    // counting picks up again from the reset value
+    clock.advance(Duration::from_secs(1));
+    tracker.update(counters(&[("eth0", 400)]));
+    assert_eq!(tracker.rates()["eth0"].received_per_sec, 300.0);
+  }
+
+  #[test]
+  fn rate_tracker_new_interface_starts_at_zero() {
+    let (mut tracker, clock) = tracker();
+    tracker.update(counters(&[("eth0", 1000)]));
+    clock.advance(Duration::from_secs(1));
+    tracker.update(counters(&[("eth0", 1500), ("wlan0", 9000)]));
+
+    assert_eq!(tracker.rates()["eth0"].received_per_sec, 500.0);
+    assert_eq!(tracker.rates()["wlan0"], NetworkRates::default());
+  }
+
+  #[test]
+  fn rate_tracker_forgets_removed_interface() {
+    let (mut tracker, clock) = tracker();
+    tracker.update(counters(&[("eth0", 1000), ("usb0", 1000)]));
+    clock.advance(Duration::from_secs(1));
+    tracker.update(counters(&[("eth0", 2000)]));
+
+    assert!(!tracker.rates().contains_key("usb0"));
+
+    // plugged back in, it starts over instead of using the old counters
+    clock.advance(Duration::from_secs(1));
+    tracker.update(counters(&[("eth0", 3000), ("usb0", 8000)]));
+    assert_eq!(tracker.rates()["usb0"], NetworkRates::default());
+  }
+
+  #[test]
+  fn rate_tracker_without_elapsed_time() {
+    let (mut tracker, _) = tracker();
+    tracker.update(counters(&[("eth0", 1000)]));
+    tracker.update(counters(&[("eth0", 2000)]));
+
+    assert_eq!(tracker.rates()["eth0"], NetworkRates::default());
+  }
+
+  #[test]
+  fn rate_tracker_total_sums_interfaces() {
+    let (mut tracker, clock) = tracker();
+    tracker.update(counters(&[("eth0", 0), ("wlan0", 0)]));
+    clock.advance(Duration::from_secs(2));
+    tracker.update(counters(&[("eth0", 1000), ("wlan0", 3000)]));
+
+    assert_eq!(tracker.total().received_per_sec, 2000.0);
+    assert_eq!(tracker.total().transmitted_per_sec, 1000.0);
+  }
+
+  #[test]
+  fn is_virtual_windows_adapters() {
+    for interface in [
+      "VMware Network Adapter VMnet8",
+      "VirtualBox Host-Only Network",
+      "vEthernet (Default Switch)",
+      "vEthernet (WSL)",
+      "Hyper-V Virtual Ethernet Adapter",
+      "TAP-Windows Adapter V9",
+      "OpenVPN Wintun",
+      "ZeroTier One [8056c2e21c000001]",
+      "Tailscale",
+    ] {
+      assert!(is_virtual(interface), "{}", interface);
+    }
+    for interface in [
+      "Ethernet",
+      "Ethernet 2",
+      "Wi-Fi",
+      "Bluetooth Network Connection",
+    ] {
+      assert!(!is_virtual(interface), "{}", interface);
+    }
+  }
+
+  #[test]
+  fn include_interface_filters() {
+    let defaults = NetworkSettings::default();
+    assert!(include_interface("eth0", &defaults));
+    assert!(include_interface("Wi-Fi", &defaults));
+    assert!(!include_interface("lo", &defaults));
+    assert!(!include_interface("Loopback Pseudo-Interface 1", &defaults));
+    assert!(!include_interface("docker0", &defaults));
+    assert!(!include_interface("vEthernet (WSL)", &defaults));
+    assert!(!include_interface("Npcap Loopback Adapter", &defaults));
+
+    let settings = NetworkSettings {
+      include_loopback: Some(true),
+      include_virtual: Some(true),
+      exclude: Some(vec!["eth0".to_string()]),
+    };
+    assert!(include_interface("lo", &settings));
+    assert!(include_interface("docker0", &settings));
+    assert!(!include_interface("eth0", &settings));
+  }
+}
@@ -0,0 +1,182 @@
+use super::widget::{Anchor, MonitorSelector, WidgetPlacement};
+
+// All rectangles are in physical pixels on the virtual desktop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+  pub x: i32,
+  pub y: i32,
+  pub width: u32,
+  pub height: u32,
+}
+
+impl Rect {
+  pub fn right(&self) -> i32 {
+    self.x + self.width as i32
+  }
+
+  pub fn bottom(&self) -> i32 {
+    self.y + self.height as i32
+  }
+
+  pub fn center(&self) -> (i32, i32) {
+    (
+      self.x + self.width as i32 / 2,
+      self.y + self.height as i32 / 2,
+    )
+  }
+
+  pub fn contains_point(&self, (x, y): (i32, i32)) -> bool {
+    x >= self.x && x < self.right() && y >= self.y && y < self.bottom()
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonitorInfo {
+  pub name: Option<String>,
+  pub rect: Rect,
+  pub scale_factor: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Align {
+  Start,
+  Center,
+  End,
+}
+
+impl Anchor {
+  fn alignment(&self) -> (Align, Align) {
+    match self {
+      Anchor::TopLeft => (Align::Start, Align::Start),
+      Anchor::Top => (Align::Center, Align::Start),
+      Anchor::TopRight => (Align::End, Align::Start),
+      Anchor::Left => (Align::Start, Align::Center),
+      Anchor::Center => (Align::Center, Align::Center),
+      Anchor::Right => (Align::End, Align::Center),
+      Anchor::BottomLeft => (Align::Start, Align::End),
+      Anchor::Bottom => (Align::Center, Align::End),
+      Anchor::BottomRight => (Align::End, Align::End),
+    }
+  }
+}
+
+// Offsets always point inwards from the anchored edge, so `(20, 20)` on a
+// bottom-right anchor keeps the widget 20px away from both edges.
+fn align(start: i32, extent: u32, size: u32, align: Align, offset: i32) -> i32 {
+  match align {
+    Align::Start => start + offset,
+    Align::Center => start + (extent as i32 - size as i32) / 2 + offset,
+    Align::End => start + extent as i32 - size as i32 - offset,
+  }
+}
+
+fn unalign(start: i32, extent: u32, size: u32, align: Align, position: i32) -> i32 {
+  match align {
+    Align::Start => position - start,
+    Align::Center => position - start - (extent as i32 - size as i32) / 2,
+    Align::End => start + extent as i32 - size as i32 - position,
+  }
+}
+
+// Monitors are expected primary first, which is also the fallback when the
+// selected monitor is not connected anymore.
+pub fn select_monitor<'a>(
+  monitors: &'a [MonitorInfo],
+  selector: Option<&MonitorSelector>,
+) -> Option<&'a MonitorInfo> {
+  let selected = match selector {
+    Some(MonitorSelector::Index(index)) => monitors.get(*index),
+    Some(MonitorSelector::Name(name)) => monitors
+      .iter()
+      .find(|monitor| monitor.name.as_deref() == Some(name.as_str())),
+    None => None,
+  };
+
+  selected.or_else(|| monitors.first())
+}
+
+pub fn clamp_to_rect(rect: Rect, bounds: Rect) -> Rect {
+  let max_x = (bounds.right() - rect.width as i32).max(bounds.x);
+  let max_y = (bounds.bottom() - rect.height as i32).max(bounds.y);
+
+  Rect {
+    x: rect.x.clamp(bounds.x, max_x),
+    y: rect.y.clamp(bounds.y, max_y),
+    ..rect
+  }
+}
+
+pub fn monitor_containing<'a>(rect: &Rect, monitors: &'a [MonitorInfo]) -> Option<&'a MonitorInfo> {
+  monitors
+    .iter()
+    .find(|monitor| monitor.rect.contains_point(rect.center()))
+}
+
+// Keeps a window fully visible on the monitor its center is on, or moves it
+// onto the primary monitor when that monitor has gone away.
+pub fn clamp_to_monitors(rect: Rect, monitors: &[MonitorInfo]) -> Rect {
+  match monitor_containing(&rect, monitors).or_else(|| monitors.first()) {
+    Some(monitor) => clamp_to_rect(rect, monitor.rect),
+    None => rect,
+  }
+}
+
+pub fn resolve_placement(
+  placement: &WidgetPlacement,
+  monitors: &[MonitorInfo],
+  size: (f64, f64),
+) -> Option<Rect> {
+  let monitor = select_monitor(monitors, placement.monitor.as_ref())?;
+  let scale_factor = monitor.scale_factor;
+
+  let width = (size.0 * scale_factor).round() as u32;
+  let height = (size.1 * scale_factor).round() as u32;
+  let (offset_x, offset_y) = placement.offset.unwrap_or((0, 0));
+  let offset_x = (offset_x as f64 * scale_factor).round() as i32;
+  let offset_y = (offset_y as f64 * scale_factor).round() as i32;
+
+  let (horizontal, vertical) = placement.anchor.alignment();
+  let bounds = monitor.rect;
+
+  let rect = Rect {
+    x: align(bounds.x, bounds.width, width, horizontal, offset_x),
+    y: align(bounds.y, bounds.height, height, vertical, offset_y),
+    width,
+    height,
+  };
+
+  Some(clamp_to_rect(rect, bounds))
+}
+
+// Inverse of `resolve_placement`: describes where a window currently is in
+// terms of the given anchor on whichever monitor it sits on.
+pub fn placement_from_rect(
+  rect: Rect,
+  monitors: &[MonitorInfo],
+  anchor: Anchor,
+) -> Option<WidgetPlacement> {
+  let (index, monitor) = monitors
+    .iter()
+    .enumerate()
+    .find(|(_, monitor)| monitor.rect.contains_point(rect.center()))
+    .or_else(|| monitors.iter().enumerate().next())?;
+
+  let (horizontal, vertical) = anchor.alignment();
+  let bounds = monitor.rect;
+  let scale_factor = monitor.scale_factor;
+
+  let offset_x = unalign(bounds.x, bounds.width, rect.width, horizontal, rect.x);
+  let offset_y = unalign(bounds.y, bounds.height, rect.height, vertical, rect.y);
+
+  Some(WidgetPlacement {
+    monitor: Some(match &monitor.name {
+      Some(name) => MonitorSelector::Name(name.clone()),
+      None => MonitorSelector::Index(index),
+    }),
+    anchor,
+    offset: Some((
+      (offset_x as f64 / scale_factor).round() as i32,
+      (offset_y as f64 / scale_factor).round() as i32,
+    )),
+  })
+}
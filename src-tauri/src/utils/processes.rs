@@ -0,0 +1,184 @@
+use std::{
+  cmp::Ordering,
+  time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, UpdateKind, Users};
+
+// Listing every process is the expensive part, so callers asking more often
+// than this get the last list again.
+pub const MIN_PROCESS_REFRESH: Duration = Duration::from_secs(2);
+pub const MAX_TOP_PROCESSES: usize = 50;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessSort {
+  Cpu,
+  Memory,
+  DiskIo,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ProcessInfo {
+  pub pid: u32,
+  pub name: String,
+  pub user: Option<String>,
+  pub command: Vec<String>,
+  // percent of one core, so it goes above 100 on multi core machines
+  pub cpu_usage: f32,
+  // bytes
+  pub memory: u64,
+  // bytes read and written since the previous refresh
+  pub disk_read: u64,
+  pub disk_written: u64,
+}
+
+impl ProcessInfo {
+  fn sort_key(&self, sort: ProcessSort) -> f64 {
+    match sort {
+      ProcessSort::Cpu => self.cpu_usage as f64,
+      ProcessSort::Memory => self.memory as f64,
+      ProcessSort::DiskIo => (self.disk_read + self.disk_written) as f64,
+    }
+  }
+}
+
+fn compare(a: &ProcessInfo, b: &ProcessInfo, sort: ProcessSort) -> Ordering {
+  b.sort_key(sort)
+    .total_cmp(&a.sort_key(sort))
+    .then(a.pid.cmp(&b.pid))
+}
+
+// Only the top `count` are sorted, the rest is just partitioned off.
+pub fn top_processes(
+  mut processes: Vec<ProcessInfo>,
+  sort: ProcessSort,
+  count: usize,
+) -> Vec<ProcessInfo> {
+  let count = count.min(MAX_TOP_PROCESSES).min(processes.len());
+  if count == 0 {
+    return Vec::new();
+  }
+
+  if count < processes.len() {
+    processes.select_nth_unstable_by(count - 1, |a, b| compare(a, b, sort));
+    processes.truncate(count);
+  }
+  processes.sort_by(|a, b| compare(a, b, sort));
+  processes
+}
+
+pub struct ProcessSampler {
+  sys: sysinfo::System,
+  users: Users,
+  refreshed_at: Option<Instant>,
+}
+
+impl Default for ProcessSampler {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl ProcessSampler {
+  pub fn new() -> Self {
+    Self {
+      sys: sysinfo::System::new(),
+      users: Users::new(),
+      refreshed_at: None,
+    }
+  }
+
+  fn refresh(&mut self) {
+    if self
+      .refreshed_at
+      .is_some_and(|at| at.elapsed() < MIN_PROCESS_REFRESH)
+    {
+      return;
+    }
+
+    // the user and command line don't change, they are read once per process
+    self.sys.refresh_processes_specifics(
+      ProcessesToUpdate::All,
+      true,
+      ProcessRefreshKind::new()
+        .with_cpu()
+        .with_memory()
+        .with_disk_usage()
+        .with_user(UpdateKind::OnlyIfNotSet)
+        .with_cmd(UpdateKind::OnlyIfNotSet),
+    );
+    if self.users.is_empty() {
+      self.users.refresh_list();
+    }
+    self.refreshed_at = Some(Instant::now());
+  }
+
+  // CPU usage is measured between two refreshes, so it reads 0 on the very
+  // first call.
+  pub fn top(&mut self, sort: ProcessSort, count: usize) -> Vec<ProcessInfo> {
+    self.refresh();
+
+    let processes = self
+      .sys
+      .processes()
+      .iter()
+      // threads show up as processes on linux
+      .filter(|(_, process)| process.thread_kind().is_none())
+      .map(|(pid, process)| {
+        let disk_usage = process.disk_usage();
+
+        ProcessInfo {
+          pid: pid.as_u32(),
+          name: process.name().to_string_lossy().to_string(),
+          user: process
+            .user_id()
+            .and_then(|uid| self.users.get_user_by_id(uid))
+            .map(|user| user.name().to_string()),
+          command: process
+            .cmd()
+            .iter()
+            .map(|arg| arg.to_string_lossy().to_string())
+            .collect(),
+          cpu_usage: process.cpu_usage(),
+          memory: process.memory(),
+          disk_read: disk_usage.read_bytes,
+          disk_written: disk_usage.written_bytes,
+        }
+      })
+      .collect();
+
+    top_processes(processes, sort, count)
+  }
+}
+
+// The name has to match what the user confirmed, so a pid that was reused
+// in the meantime isn't killed by mistake.
+pub fn end_process(pid: u32, name: &str) -> Result<(), String> {
+  if pid == std::process::id() {
+    return Err("refusing to end the widgets app itself".to_string());
+  }
+
+  let pid = Pid::from_u32(pid);
+  let mut sys = sysinfo::System::new();
+  sys.refresh_processes(ProcessesToUpdate::Some(&[pid]), true);
+
+  let process = sys
+    .process(pid)
+    .ok_or_else(|| format!("process {} is not running", pid))?;
+
+  if process.name().to_string_lossy() != name {
+    return Err(format!(
+      "process {} is now {:?}, not {:?}",
+      pid,
+      process.name(),
+      name
+    ));
+  }
+
+  if process.kill() {
+    Ok(())
+  } else {
+    Err(format!("failed to end process {}", pid))
+  }
+}
@@ -0,0 +1,82 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::widget::{Widget, WidgetAppearance, WidgetPlacement};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ProfileWidget {
+  pub position: Option<(i32, i32)>,
+  pub placement: Option<WidgetPlacement>,
+  pub size: Option<(u32, u32)>,
+  pub appearance: Option<WidgetAppearance>,
+}
+
+// Only the widgets listed in a profile are opened while it is active.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Profile {
+  pub widgets: BTreeMap<String, ProfileWidget>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ProfileStore {
+  pub active: Option<String>,
+  pub profiles: BTreeMap<String, Profile>,
+}
+
+impl ProfileStore {
+  pub fn active_profile(&self) -> Option<&Profile> {
+    self
+      .active
+      .as_ref()
+      .and_then(|name| self.profiles.get(name))
+  }
+
+  pub fn active_profile_mut(&mut self) -> Option<&mut Profile> {
+    let name = self.active.clone()?;
+    self.profiles.get_mut(&name)
+  }
+}
+
+pub fn apply_profile(widgets: &[Widget], profile: &Profile) -> Vec<Widget> {
+  widgets
+    .iter()
+    .filter_map(|widget| {
+      let entry = profile.widgets.get(&widget.id)?;
+      let mut widget = widget.clone();
+
+      // a profile position replaces the placement and the other way round
+      if entry.position.is_some() || entry.placement.is_some() {
+        widget.property.position = entry.position;
+        widget.property.placement = entry.placement.clone();
+      }
+      if entry.size.is_some() {
+        widget.property.size = entry.size;
+      }
+      if let Some(appearance) = &entry.appearance {
+        widget.appearance = appearance.overlay(&widget.appearance);
+      }
+
+      Some(widget)
+    })
+    .collect()
+}
+
+pub fn capture_profile(widgets: &[Widget]) -> Profile {
+  Profile {
+    widgets: widgets
+      .iter()
+      .map(|widget| {
+        (
+          widget.id.clone(),
+          ProfileWidget {
+            position: widget.property.position,
+            placement: widget.property.placement.clone(),
+            size: widget.property.size,
+            appearance: None,
+          },
+        )
+      })
+      .collect(),
+  }
+}
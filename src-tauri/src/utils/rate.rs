@@ -0,0 +1,52 @@
+use std::time::{Duration, Instant};
+
+// Lets the rate math run against a fake clock.
+pub trait Clock {
+  fn now(&self) -> Instant;
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+  fn now(&self) -> Instant {
+    Instant::now()
+  }
+}
+
+// A counter that went down was reset, e.g. an interface came back up, so
+// nothing is counted for that interval.
+pub fn per_second(previous: u64, current: u64, elapsed: Duration) -> f64 {
+  let seconds = elapsed.as_secs_f64();
+  if seconds == 0.0 || current < previous {
+    return 0.0;
+  }
+
+  (current - previous) as f64 / seconds
+}
+
+// A clock the trackers' tests move forward by hand.
+#[cfg(test)]
+#[derive(Debug, Clone)]
+pub struct FakeClock(std::rc::Rc<std::cell::Cell<Instant>>);
+
+#[cfg(test)]
+impl Default for FakeClock {
+  fn default() -> Self {
+    Self(std::rc::Rc::new(std::cell::Cell::new(Instant::now())))
+  }
+}
+
+#[cfg(test)]
+impl FakeClock {
+  pub fn advance(&self, by: Duration) {
+    self.0.set(self.0.get() + by);
+  }
+}
+
+#[cfg(test)]
+impl Clock for FakeClock {
+  fn now(&self) -> Instant {
+    self.0.get()
+  }
+}
@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutoHideRule {
+  FullscreenApp,
+  OnBattery,
+}
+
+// Snapshot of the system state the rules are checked against.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Conditions {
+  pub fullscreen_app: bool,
+  pub on_battery: bool,
+}
+
+impl AutoHideRule {
+  pub fn matches(&self, conditions: &Conditions) -> bool {
+    match self {
+      AutoHideRule::FullscreenApp => conditions.fullscreen_app,
+      AutoHideRule::OnBattery => conditions.on_battery,
+    }
+  }
+}
+
+pub fn auto_hidden(rules: &[AutoHideRule], conditions: &Conditions) -> bool {
+  rules.iter().any(|rule| rule.matches(conditions))
+}
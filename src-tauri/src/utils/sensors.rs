@@ -0,0 +1,293 @@
+use std::{
+  fs,
+  path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+use systemstat::Platform;
+
+use super::sysfs::read_trimmed;
+
+pub const HWMON_ROOT: &str = "/sys/class/hwmon";
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Temperature {
+  pub label: String,
+  pub celsius: f32,
+  pub max: Option<f32>,
+  pub critical: Option<f32>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Fan {
+  pub label: String,
+  pub rpm: u32,
+}
+
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub struct SensorReadings {
+  pub temperatures: Vec<Temperature>,
+  pub fans: Vec<Fan>,
+}
+
+fn read_number(path: &Path) -> Option<i64> {
+  read_trimmed(path)?.parse().ok()
+}
+
+// hwmon reports temperatures in millidegrees celsius.
+fn read_millidegrees(path: &Path) -> Option<f32> {
+  read_number(path).map(|value| value as f32 / 1000.0)
+}
+
+// Indices of the `{prefix}N_input` files in a hwmon device, sorted.
+fn input_indices(device: &Path, prefix: &str) -> Vec<u32> {
+  let Ok(entries) = fs::read_dir(device) else {
+    return Vec::new();
+  };
+
+  let mut indices: Vec<u32> = entries
+    .flatten()
+    .filter_map(|entry| {
+      let name = entry.file_name().into_string().ok()?;
+      name
+        .strip_prefix(prefix)?
+        .strip_suffix("_input")?
+        .parse()
+        .ok()
+    })
+    .collect();
+  indices.sort_unstable();
+  indices
+}
+
+// `{chip} {label}`, falling back to the sensor file name when the chip
+// doesn't label its inputs.
+fn sensor_label(device: &Path, chip: &str, sensor: &str) -> String {
+  let label =
+    read_trimmed(&device.join(format!("{}_label", sensor))).unwrap_or_else(|| sensor.to_string());
+
+  if chip.is_empty() {
+    label
+  } else {
+    format!("{} {}", chip, label)
+  }
+}
+
+fn hwmon_devices(root: &Path) -> Vec<PathBuf> {
+  let Ok(entries) = fs::read_dir(root) else {
+    return Vec::new();
+  };
+
+  let mut devices: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).collect();
+  devices.sort();
+  devices
+}
+
+// Reads every temperature and fan input under a hwmon root, normally
+// `/sys/class/hwmon`. Inputs that can't be read, e.g. a sensor that is
+// powered down, are skipped.
+pub fn read_hwmon(root: &Path) -> SensorReadings {
+  let mut readings = SensorReadings::default();
+
+  for device in hwmon_devices(root) {
+    let chip = read_trimmed(&device.join("name")).unwrap_or_default();
+
+    for index in input_indices(&device, "temp") {
+      let sensor = format!("temp{}", index);
+      let Some(celsius) = read_millidegrees(&device.join(format!("{}_input", sensor))) else {
+        continue;
+      };
+
+      readings.temperatures.push(Temperature {
+        label: sensor_label(&device, &chip, &sensor),
+        celsius,
+        max: read_millidegrees(&device.join(format!("{}_max", sensor))),
+        critical: read_millidegrees(&device.join(format!("{}_crit", sensor))),
+      });
+    }
+
+    for index in input_indices(&device, "fan") {
+      let sensor = format!("fan{}", index);
+      let Some(rpm) = read_number(&device.join(format!("{}_input", sensor))) else {
+        continue;
+      };
+
+      readings.fans.push(Fan {
+        label: sensor_label(&device, &chip, &sensor),
+        rpm: rpm.max(0) as u32,
+      });
+    }
+  }
+
+  readings
+}
+
+// sysinfo reports NaN for temperatures it couldn't read and 0 for unknown
+// thresholds.
+pub fn read_components(components: &sysinfo::Components) -> Vec<Temperature> {
+  components
+    .iter()
+    .filter(|component| component.temperature().is_finite())
+    .map(|component| Temperature {
+      label: component.label().to_string(),
+      celsius: component.temperature(),
+      max: Some(component.max()).filter(|max| max.is_finite() && *max > 0.0),
+      critical: component.critical().filter(|critical| *critical > 0.0),
+    })
+    .collect()
+}
+
+// On Linux hwmon has everything including the fans. Elsewhere sysinfo's
+// components are used, with systemstat's CPU temperature as a last resort.
+// The components are only refreshed when they are needed.
+pub fn read_sensors(components: &mut sysinfo::Components) -> SensorReadings {
+  if cfg!(target_os = "linux") {
+    let readings = read_hwmon(Path::new(HWMON_ROOT));
+    if !readings.temperatures.is_empty() || !readings.fans.is_empty() {
+      return readings;
+    }
+  }
+
+  if components.is_empty() {
+    components.refresh_list();
+  } else {
+    components.refresh();
+  }
+
+  let mut temperatures = read_components(components);
+  if temperatures.is_empty() {
+    if let Ok(celsius) = systemstat::System::new().cpu_temp() {
+      temperatures.push(Temperature {
+        label: "CPU".to_string(),
+        celsius,
+        max: None,
+        critical: None,
+      });
+    }
+  }
+
+  SensorReadings {
+    temperatures,
+    fans: Vec::new(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::utils::test_dir::{temp_dir, write};
+
+  #[test]
+  fn read_hwmon_temperatures_with_labels_and_limits() {
+    let root = temp_dir("hwmon-temperatures");
+    let device = root.join("hwmon0");
+    write(&device, "name", "coretemp\n");
+    write(&device, "temp1_input", "45000\n");
+    write(&device, "temp1_label", "Package id 0\n");
+    write(&device, "temp1_max", "84000\n");
+    write(&device, "temp1_crit", "100000\n");
+    write(&device, "temp2_input", "51500");
+
+    let readings = read_hwmon(&root);
+
+    assert_eq!(
+      readings.temperatures,
+      [
+        Temperature {
+          label: "coretemp Package id 0".to_string(),
+          celsius: 45.0,
+          max: Some(84.0),
+          critical: Some(100.0),
+        },
+        Temperature {
+          label: "coretemp temp2".to_string(),
+          celsius: 51.5,
+          max: None,
+          critical: None,
+        },
+      ]
+    );
+    let _ = fs::remove_dir_all(&root);
+  }
+
+  #[test]
+  fn read_hwmon_without_chip_name() {
+    let root = temp_dir("hwmon-unnamed");
+    write(&root.join("hwmon0"), "temp1_input", "30000");
+
+    assert_eq!(read_hwmon(&root).temperatures[0].label, "temp1");
+    let _ = fs::remove_dir_all(&root);
+  }
+
+  #[test]
+  fn read_hwmon_fans() {
+    let root = temp_dir("hwmon-fans");
+    let device = root.join("hwmon2");
+    write(&device, "name", "nct6775");
+    write(&device, "fan1_input", "1200\n");
+    write(&device, "fan1_label", "CPU Fan");
+    write(&device, "fan2_input", "0");
+
+    assert_eq!(
+      read_hwmon(&root).fans,
+      [
+        Fan {
+          label: "nct6775 CPU Fan".to_string(),
+          rpm: 1200,
+        },
+        Fan {
+          label: "nct6775 fan2".to_string(),
+          rpm: 0,
+        },
+      ]
+    );
+    let _ = fs::remove_dir_all(&root);
+  }
+
+  #[test]
+  fn read_hwmon_skips_unreadable_inputs() {
+    let root = temp_dir("hwmon-unreadable");
+    let device = root.join("hwmon0");
+    write(&device, "name", "acpitz");
+    write(&device, "temp1_input", "garbage");
+    write(&device, "temp2_input", "");
+    // a powered down sensor fails to read
+    fs::create_dir_all(device.join("temp3_input")).unwrap();
+    write(&device, "temp4_input", "40000");
+    write(&device, "fan1_input", "n/a");
+
+    let readings = read_hwmon(&root);
+
+    assert_eq!(readings.temperatures.len(), 1);
+    assert_eq!(readings.temperatures[0].label, "acpitz temp4");
+    assert!(readings.fans.is_empty());
+    let _ = fs::remove_dir_all(&root);
+  }
+
+  #[test]
+  fn read_hwmon_sorts_devices_and_inputs() {
+    let root = temp_dir("hwmon-sorted");
+    write(&root.join("hwmon1"), "name", "nvme");
+    write(&root.join("hwmon1"), "temp1_input", "35000");
+    write(&root.join("hwmon0"), "name", "k10temp");
+    write(&root.join("hwmon0"), "temp10_input", "50000");
+    write(&root.join("hwmon0"), "temp2_input", "50000");
+
+    let labels: Vec<String> = read_hwmon(&root)
+      .temperatures
+      .into_iter()
+      .map(|temperature| temperature.label)
+      .collect();
+
+    assert_eq!(labels, ["k10temp temp2", "k10temp temp10", "nvme temp1"]);
+    let _ = fs::remove_dir_all(&root);
+  }
+
+  #[test]
+  fn read_hwmon_without_root() {
+    let root = temp_dir("hwmon-missing");
+
+    assert_eq!(read_hwmon(&root.join("hwmon")), SensorReadings::default());
+    let _ = fs::remove_dir_all(&root);
+  }
+}
@@ -0,0 +1,89 @@
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_SNAP_THRESHOLD: u32 = 12;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModifierKey {
+  Alt,
+  Ctrl,
+  Shift,
+  Super,
+}
+
+// Grid size and threshold are in logical pixels.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct SnapSettings {
+  pub grid: Option<u32>,
+  pub threshold: Option<u32>,
+  // snap to the edges of other widgets and the screen
+  pub edges: Option<bool>,
+}
+
+impl SnapSettings {
+  pub fn threshold(&self) -> u32 {
+    self.threshold.unwrap_or(DEFAULT_SNAP_THRESHOLD)
+  }
+
+  pub fn edges(&self) -> bool {
+    self.edges.unwrap_or(true)
+  }
+}
+
+// Which interfaces count towards the network rates and totals.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct NetworkSettings {
+  pub include_loopback: Option<bool>,
+  pub include_virtual: Option<bool>,
+  // interface names that are always left out
+  pub exclude: Option<Vec<String>>,
+}
+
+impl NetworkSettings {
+  pub fn include_loopback(&self) -> bool {
+    self.include_loopback.unwrap_or(false)
+  }
+
+  pub fn include_virtual(&self) -> bool {
+    self.include_virtual.unwrap_or(false)
+  }
+}
+
+// A disk is low on space when it falls under either threshold.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct DiskAlertSettings {
+  pub min_free_percent: Option<f64>,
+  pub min_free_bytes: Option<u64>,
+  // every disk is watched when unset
+  pub mount_points: Option<Vec<String>>,
+}
+
+impl DiskAlertSettings {
+  pub fn watches(&self, mount_point: &str) -> bool {
+    self
+      .mount_points
+      .as_ref()
+      .is_none_or(|mount_points| mount_points.iter().any(|watched| watched == mount_point))
+  }
+}
+
+// App wide toggles that apply on top of every widget's own properties.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Settings {
+  pub locked: Option<bool>,
+  pub click_through: Option<bool>,
+  // holding this key over a click-through widget makes it interactive
+  pub hover_modifier: Option<ModifierKey>,
+  pub snap: Option<SnapSettings>,
+  pub network: Option<NetworkSettings>,
+  pub disk_alerts: Option<DiskAlertSettings>,
+}
+
+impl Settings {
+  pub fn locked(&self) -> bool {
+    self.locked.unwrap_or(false)
+  }
+
+  pub fn click_through(&self) -> bool {
+    self.click_through.unwrap_or(false)
+  }
+}
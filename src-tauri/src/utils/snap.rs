@@ -0,0 +1,313 @@
+use super::placement::Rect;
+
+// Everything is in physical pixels, like the rectangles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SnapOptions {
+  pub grid: Option<u32>,
+  pub threshold: u32,
+  pub edges: bool,
+}
+
+fn snap_to_grid(position: i32, origin: i32, grid: u32) -> i32 {
+  let grid = grid as f64;
+  origin + (((position - origin) as f64 / grid).round() * grid) as i32
+}
+
+fn nearest(position: i32, candidates: impl Iterator<Item = i32>, threshold: u32) -> Option<i32> {
+  candidates
+    .filter(|candidate| candidate.abs_diff(position) <= threshold)
+    .min_by_key(|candidate| candidate.abs_diff(position))
+}
+
+// Two spans are close enough to snap against each other when they overlap
+// or are at most `threshold` apart.
+fn spans_near(start: i32, end: i32, other_start: i32, other_end: i32, threshold: u32) -> bool {
+  let threshold = threshold as i32;
+  start <= other_end + threshold && other_start <= end + threshold
+}
+
+// Candidate positions along one axis for a window of `size`: flush with the
+// inside of every monitor edge, and next to or aligned with every other
+// widget's edges.
+fn edge_candidates(size: u32, monitors: &[(i32, i32)], others: &[(i32, i32)]) -> Vec<i32> {
+  let size = size as i32;
+
+  monitors
+    .iter()
+    .flat_map(|(start, end)| [*start, end - size])
+    .chain(
+      others
+        .iter()
+        .flat_map(|(start, end)| [*end, start - size, *start, end - size]),
+    )
+    .collect()
+}
+
+// Snaps the top-left corner of `rect` to the grid of the monitor it is on,
+// then to the closest edge within the threshold on each axis. Edges win over
+// the grid so widgets can sit flush against each other on any grid size.
+pub fn snap_rect(rect: Rect, others: &[Rect], monitors: &[Rect], options: &SnapOptions) -> Rect {
+  let mut snapped = rect;
+
+  let bounds = monitors
+    .iter()
+    .find(|monitor| monitor.contains_point(rect.center()))
+    .or_else(|| monitors.first());
+
+  if let (Some(grid), Some(bounds)) = (options.grid.filter(|grid| *grid > 0), bounds) {
+    snapped.x = snap_to_grid(rect.x, bounds.x, grid);
+    snapped.y = snap_to_grid(rect.y, bounds.y, grid);
+  }
+
+  if !options.edges {
+    return snapped;
+  }
+
+  let monitors_x: Vec<(i32, i32)> = monitors.iter().map(|m| (m.x, m.right())).collect();
+  let monitors_y: Vec<(i32, i32)> = monitors.iter().map(|m| (m.y, m.bottom())).collect();
+
+  // only widgets that are level with the dragged one count on each axis
+  let others_x: Vec<(i32, i32)> = others
+    .iter()
+    .filter(|other| {
+      spans_near(
+        rect.y,
+        rect.bottom(),
+        other.y,
+        other.bottom(),
+        options.threshold,
+      )
+    })
+    .map(|other| (other.x, other.right()))
+    .collect();
+  let others_y: Vec<(i32, i32)> = others
+    .iter()
+    .filter(|other| {
+      spans_near(
+        rect.x,
+        rect.right(),
+        other.x,
+        other.right(),
+        options.threshold,
+      )
+    })
+    .map(|other| (other.y, other.bottom()))
+    .collect();
+
+  let candidates_x = edge_candidates(rect.width, &monitors_x, &others_x);
+  if let Some(x) = nearest(rect.x, candidates_x.into_iter(), options.threshold) {
+    snapped.x = x;
+  }
+
+  let candidates_y = edge_candidates(rect.height, &monitors_y, &others_y);
+  if let Some(y) = nearest(rect.y, candidates_y.into_iter(), options.threshold) {
+    snapped.y = y;
+  }
+
+  snapped
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn rect(x: i32, y: i32, width: u32, height: u32) -> Rect {
+    Rect {
+      x,
+      y,
+      width,
+      height,
+    }
+  }
+
+  fn options(grid: Option<u32>, edges: bool) -> SnapOptions {
+    SnapOptions {
+      grid,
+      threshold: 10,
+      edges,
+    }
+  }
+
+  const MONITOR: Rect = Rect {
+    x: 0,
+    y: 0,
+    width: 1920,
+    height: 1080,
+  };
+
+  #[test]
+  fn snap_rect_rounds_to_grid() {
+    let snapped = snap_rect(
+      rect(130, 170, 100, 100),
+      &[],
+      &[MONITOR],
+      &options(Some(50), false),
+    );
+
+    assert_eq!(snapped, rect(150, 150, 100, 100));
+  }
+
+  #[test]
+  fn snap_rect_ignores_zero_grid() {
+    let dropped = rect(130, 170, 100, 100);
+
+    assert_eq!(
+      snap_rect(dropped, &[], &[MONITOR], &options(Some(0), false)),
+      dropped
+    );
+  }
+
+  #[test]
+  fn snap_rect_snaps_to_monitor_edges_within_threshold() {
+    let monitors = [MONITOR];
+
+    assert_eq!(
+      snap_rect(
+        rect(10, 990, 100, 100),
+        &[],
+        &monitors,
+        &options(None, true)
+      ),
+      rect(0, 980, 100, 100)
+    );
+    assert_eq!(
+      snap_rect(
+        rect(1811, 500, 100, 100),
+        &[],
+        &monitors,
+        &options(None, true)
+      ),
+      rect(1820, 500, 100, 100)
+    );
+    // one pixel past the threshold stays put
+    assert_eq!(
+      snap_rect(
+        rect(11, 500, 100, 100),
+        &[],
+        &monitors,
+        &options(None, true)
+      ),
+      rect(11, 500, 100, 100)
+    );
+  }
+
+  #[test]
+  fn snap_rect_snaps_next_to_and_aligned_with_widgets() {
+    let others = [rect(200, 500, 100, 100)];
+
+    // right next to the other widget, tops aligned
+    assert_eq!(
+      snap_rect(
+        rect(306, 507, 100, 100),
+        &others,
+        &[MONITOR],
+        &options(None, true)
+      ),
+      rect(300, 500, 100, 100)
+    );
+    // below it, left edges aligned
+    assert_eq!(
+      snap_rect(
+        rect(195, 604, 100, 100),
+        &others,
+        &[MONITOR],
+        &options(None, true)
+      ),
+      rect(200, 600, 100, 100)
+    );
+  }
+
+  #[test]
+  fn snap_rect_prefers_edges_over_grid() {
+    let others = [rect(0, 500, 300, 100)];
+
+    // the grid alone would put it at 320, 512
+    assert_eq!(
+      snap_rect(
+        rect(302, 500, 100, 100),
+        &others,
+        &[MONITOR],
+        &options(Some(64), true)
+      ),
+      rect(300, 500, 100, 100)
+    );
+  }
+
+  #[test]
+  fn snap_rect_only_uses_level_widgets() {
+    // far above, so its right edge doesn't count
+    let above = [rect(200, 100, 100, 100)];
+    assert_eq!(
+      snap_rect(
+        rect(306, 800, 100, 100),
+        &above,
+        &[MONITOR],
+        &options(None, true)
+      ),
+      rect(306, 800, 100, 100)
+    );
+
+    // a gap within the threshold still counts as level, and the top snaps
+    // to its bottom
+    let near = [rect(200, 300, 100, 100)];
+    assert_eq!(
+      snap_rect(
+        rect(306, 408, 100, 100),
+        &near,
+        &[MONITOR],
+        &options(None, true)
+      ),
+      rect(300, 400, 100, 100)
+    );
+  }
+
+  #[test]
+  fn snap_rect_uses_monitor_under_center() {
+    let monitors = [MONITOR, rect(1910, 0, 1280, 1024)];
+
+    // the grid starts at the corner of the second monitor
+    assert_eq!(
+      snap_rect(
+        rect(1990, 300, 100, 100),
+        &[],
+        &monitors,
+        &options(Some(50), false)
+      ),
+      rect(2010, 300, 100, 100)
+    );
+    // the inside edge of the second monitor
+    assert_eq!(
+      snap_rect(
+        rect(3085, 300, 100, 100),
+        &[],
+        &monitors,
+        &options(None, true)
+      ),
+      rect(3090, 300, 100, 100)
+    );
+  }
+
+  #[test]
+  fn snap_rect_falls_back_to_first_monitor() {
+    let monitors = [rect(100, 100, 800, 600), rect(900, 100, 800, 600)];
+
+    assert_eq!(
+      snap_rect(
+        rect(-500, -500, 100, 100),
+        &[],
+        &monitors,
+        &options(Some(50), false)
+      ),
+      rect(-500, -500, 100, 100)
+    );
+    assert_eq!(
+      snap_rect(
+        rect(-480, -520, 100, 100),
+        &[],
+        &monitors,
+        &options(Some(50), false)
+      ),
+      rect(-500, -500, 100, 100)
+    );
+  }
+}
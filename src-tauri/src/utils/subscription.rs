@@ -0,0 +1,153 @@
+use std::{
+  collections::{HashMap, HashSet},
+  time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::widget::SystemMetric;
+
+// Nothing is emitted more often than this, it's also how often the fetcher
+// checks which subscribers are due.
+pub const MIN_SUBSCRIPTION_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MetricSubscription {
+  pub metric: SystemMetric,
+  pub interval_ms: u64,
+}
+
+impl MetricSubscription {
+  pub fn interval(&self) -> Duration {
+    Duration::from_millis(self.interval_ms).max(MIN_SUBSCRIPTION_INTERVAL)
+  }
+}
+
+#[derive(Debug, Clone)]
+struct Subscriber {
+  // the window samples are emitted to, a widget inside a container shares
+  // the container's window
+  window: String,
+  intervals: HashMap<SystemMetric, Duration>,
+  emitted_at: HashMap<SystemMetric, Instant>,
+}
+
+// The metrics one subscriber should get on this tick.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DueSubscriber {
+  pub subscriber: String,
+  pub window: String,
+  pub metrics: HashSet<SystemMetric>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Subscriptions {
+  subscribers: HashMap<String, Subscriber>,
+}
+
+impl Subscriptions {
+  // Replaces whatever the subscriber was subscribed to before. Subscribing to
+  // the same metric twice keeps the shorter interval.
+  pub fn subscribe(
+    &mut self,
+    subscriber: &str,
+    window: &str,
+    subscriptions: &[MetricSubscription],
+  ) {
+    if subscriptions.is_empty() {
+      self.unsubscribe(subscriber);
+      return;
+    }
+
+    let mut intervals: HashMap<SystemMetric, Duration> = HashMap::new();
+    for subscription in subscriptions {
+      intervals
+        .entry(subscription.metric)
+        .and_modify(|interval| *interval = (*interval).min(subscription.interval()))
+        .or_insert(subscription.interval());
+    }
+
+    self.subscribers.insert(
+      subscriber.to_string(),
+      Subscriber {
+        window: window.to_string(),
+        intervals,
+        emitted_at: HashMap::new(),
+      },
+    );
+  }
+
+  pub fn unsubscribe(&mut self, subscriber: &str) -> bool {
+    self.subscribers.remove(subscriber).is_some()
+  }
+
+  pub fn retain(&mut self, mut keep: impl FnMut(&str) -> bool) {
+    self.subscribers.retain(|subscriber, _| keep(subscriber));
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.subscribers.is_empty()
+  }
+
+  pub fn metrics(&self) -> HashSet<SystemMetric> {
+    self
+      .subscribers
+      .values()
+      .flat_map(|subscriber| subscriber.intervals.keys().copied())
+      .collect()
+  }
+
+  // Each metric is sampled at the shortest interval anyone asked for.
+  pub fn intervals(&self) -> HashMap<SystemMetric, Duration> {
+    let mut merged: HashMap<SystemMetric, Duration> = HashMap::new();
+
+    for (metric, interval) in self
+      .subscribers
+      .values()
+      .flat_map(|subscriber| subscriber.intervals.iter())
+    {
+      merged
+        .entry(*metric)
+        .and_modify(|merged| *merged = (*merged).min(*interval))
+        .or_insert(*interval);
+    }
+
+    merged
+  }
+
+  // Returns the subscribers with at least one metric whose interval has
+  // passed at `now`, and marks those metrics as emitted.
+  pub fn take_due(&mut self, now: Instant) -> Vec<DueSubscriber> {
+    let mut due = Vec::new();
+
+    for (id, subscriber) in self.subscribers.iter_mut() {
+      let metrics: HashSet<SystemMetric> = subscriber
+        .intervals
+        .iter()
+        .filter(|(metric, interval)| {
+          subscriber
+            .emitted_at
+            .get(metric)
+            .is_none_or(|at| now.saturating_duration_since(*at) >= **interval)
+        })
+        .map(|(metric, _)| *metric)
+        .collect();
+
+      if metrics.is_empty() {
+        continue;
+      }
+
+      for metric in &metrics {
+        subscriber.emitted_at.insert(*metric, now);
+      }
+
+      due.push(DueSubscriber {
+        subscriber: id.clone(),
+        window: subscriber.window.clone(),
+        metrics,
+      });
+    }
+
+    due
+  }
+}
@@ -0,0 +1,8 @@
+use std::{fs, path::Path};
+
+// sysfs attributes end in a newline.
+pub fn read_trimmed(path: &Path) -> Option<String> {
+  fs::read_to_string(path)
+    .ok()
+    .map(|contents| contents.trim().to_string())
+}
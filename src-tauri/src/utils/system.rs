@@ -1,6 +1,25 @@
-use std::{collections::HashMap, net::IpAddr};
-
-use tauri::{async_runtime, App, Emitter};
+use std::{
+  collections::{HashMap, HashSet},
+  net::IpAddr,
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+  },
+  time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use tauri::{AppHandle, Emitter, Runtime};
+
+use super::{
+  diskstats::{self, DiskIo, DiskIoTracker},
+  gpu::{self, GpuInfo, GpuSource, GpuSupport},
+  history::{MetricHistory, MetricSeries, Point},
+  network::{self, NetworkCounters, NetworkRates, RateTracker},
+  sensors::{self, SensorReadings},
+  settings::NetworkSettings,
+  subscription::{MetricSubscription, Subscriptions, MIN_SUBSCRIPTION_INTERVAL},
+  widget::SystemMetric,
+};
 
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct RamInfo {
@@ -18,15 +37,8 @@ pub struct DiskInfo {
   pub mount_point: String,
   pub total_space: u64,
   pub available_space: u64,
-}
-
-#[derive(Debug, Clone, serde::Serialize)]
-pub struct GpuInfo {
-  pub id: String,
-  pub memory_usage: u32,
-  pub encoder_usage: u32,
-  pub decoder_usage: u32,
-  pub global_utilization: u32,
+  // the physical device the partition is on, where it can be told
+  pub device: Option<String>,
 }
 
 #[derive(Debug, Clone, serde::Serialize)]
@@ -37,20 +49,29 @@ pub struct IpNetworksInfo {
 
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct NetworkInfo {
-  received: u64,
-  total_received: u64,
-  transmitted: u64,
-  total_transmitted: u64,
-  packets_received: u64,
-  total_packets_received: u64,
-  packets_transmitted: u64,
-  total_packets_transmitted: u64,
-  errors_on_received: u64,
-  total_errors_on_received: u64,
-  errors_on_transmitted: u64,
-  total_errors_on_transmitted: u64,
-  mac_address: sysinfo::MacAddr,
-  ip_networks: Vec<IpNetworksInfo>,
+  pub received: u64,
+  pub total_received: u64,
+  pub transmitted: u64,
+  pub total_transmitted: u64,
+  pub packets_received: u64,
+  pub total_packets_received: u64,
+  pub packets_transmitted: u64,
+  pub total_packets_transmitted: u64,
+  pub errors_on_received: u64,
+  pub total_errors_on_received: u64,
+  pub errors_on_transmitted: u64,
+  pub total_errors_on_transmitted: u64,
+  pub mac_address: sysinfo::MacAddr,
+  pub ip_networks: Vec<IpNetworksInfo>,
+  #[serde(flatten)]
+  pub rates: NetworkRates,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct NetworkSummary {
+  pub interfaces: HashMap<String, NetworkInfo>,
+  // over the interfaces above, the filtered out ones don't count
+  pub total: NetworkRates,
 }
 
 #[derive(Debug, Clone, serde::Serialize)]
@@ -67,15 +88,27 @@ pub struct BasicMachineInfo {
 
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct ChangingMachineInfo {
-  pub cpu_usage: Vec<f32>,
-  pub gpu_usage: Vec<GpuInfo>,
-  pub ram_usage: RamInfo,
-  pub disk_usage: Vec<DiskInfo>,
+  pub cpu_usage: Option<Vec<f32>>,
+  pub gpu_usage: Option<Vec<GpuInfo>>,
+  pub gpu_support: Option<GpuSupport>,
+  pub ram_usage: Option<RamInfo>,
+  pub disk_usage: Option<Vec<DiskInfo>>,
+  // only on linux, from /proc/diskstats
+  pub disk_io: Option<Vec<DiskIo>>,
+  pub sensors: Option<SensorReadings>,
 }
 
-pub fn get_cpu_info(sys: &mut sysinfo::System) -> Vec<f32> {
-  sys.refresh_all();
+// Emitted to a subscriber's window with only the metrics due for it, the
+// others are left empty.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SystemSample {
+  pub subscriber: String,
+  #[serde(flatten)]
+  pub machine: ChangingMachineInfo,
+  pub network: Option<NetworkSummary>,
+}
 
+pub fn get_cpu_info(sys: &sysinfo::System) -> Vec<f32> {
   let mut cpu_usage = Vec::new();
   for cpu in sys.cpus().iter() {
     cpu_usage.push(cpu.cpu_usage());
@@ -84,26 +117,7 @@ pub fn get_cpu_info(sys: &mut sysinfo::System) -> Vec<f32> {
   cpu_usage
 }
 
-pub fn get_gpu_info(machine: &mut machine_info::Machine) -> Vec<GpuInfo> {
-  let details = machine.graphics_status();
-
-  let mut gpu_usage = Vec::new();
-  for gpu in details {
-    gpu_usage.push(GpuInfo {
-      id: gpu.id.clone(),
-      memory_usage: gpu.memory_usage,
-      encoder_usage: gpu.encoder,
-      decoder_usage: gpu.decoder,
-      global_utilization: gpu.gpu,
-    });
-  }
-
-  gpu_usage
-}
-
-pub fn get_ram_info(sys: &mut sysinfo::System) -> RamInfo {
-  sys.refresh_all();
-
+pub fn get_ram_info(sys: &sysinfo::System) -> RamInfo {
   RamInfo {
     total: sys.total_memory(),
     used: sys.used_memory(),
@@ -112,14 +126,15 @@ pub fn get_ram_info(sys: &mut sysinfo::System) -> RamInfo {
   }
 }
 
-pub fn get_disk_info(partitions: &mut sysinfo::Disks) -> Vec<DiskInfo> {
-  partitions.refresh_list();
-
+pub fn get_disk_info(partitions: &sysinfo::Disks) -> Vec<DiskInfo> {
   let mut disks = Vec::new();
 
   for partition in partitions.iter() {
+    let name = partition.name().to_os_string().into_string().unwrap();
+
     disks.push(DiskInfo {
-      name: partition.name().to_os_string().into_string().unwrap(),
+      device: name.strip_prefix("/dev/").map(diskstats::physical_device),
+      name,
       kind: partition.kind().to_string(),
       fs: partition
         .file_system()
@@ -139,18 +154,45 @@ pub fn get_disk_info(partitions: &mut sysinfo::Disks) -> Vec<DiskInfo> {
   disks
 }
 
-pub fn get_network_info(net: &mut sysinfo::Networks) -> HashMap<String, NetworkInfo> {
-  net.refresh_list();
+pub fn get_network_counters(
+  net: &sysinfo::Networks,
+  settings: &NetworkSettings,
+) -> HashMap<String, NetworkCounters> {
+  net
+    .iter()
+    .filter(|(interface, _)| network::include_interface(interface, settings))
+    .map(|(interface, network)| {
+      (
+        interface.clone(),
+        NetworkCounters {
+          received: network.total_received(),
+          transmitted: network.total_transmitted(),
+          packets_received: network.total_packets_received(),
+          packets_transmitted: network.total_packets_transmitted(),
+        },
+      )
+    })
+    .collect()
+}
 
+// Only the interfaces the rates were computed for are included.
+pub fn get_network_info(
+  net: &sysinfo::Networks,
+  rates: &HashMap<String, NetworkRates>,
+) -> HashMap<String, NetworkInfo> {
   let mut network_info = HashMap::new();
 
   for (interface, network) in net.iter() {
+    let Some(rates) = rates.get(interface) else {
+      continue;
+    };
+
     let mut ip_networks = Vec::new();
 
     for ip in network.ip_networks() {
       ip_networks.push(IpNetworksInfo {
-        ip: ip.clone().addr,
-        mask: ip.clone().prefix,
+        ip: ip.addr,
+        mask: ip.prefix,
       });
     }
 
@@ -171,6 +213,7 @@ pub fn get_network_info(net: &mut sysinfo::Networks) -> HashMap<String, NetworkI
         total_errors_on_transmitted: network.total_errors_on_transmitted(),
         mac_address: network.mac_address(),
         ip_networks,
+        rates: *rates,
       },
     );
   }
@@ -199,77 +242,405 @@ pub fn get_basic_machine_info() -> BasicMachineInfo {
   }
 }
 
-pub fn get_changing_machine_info() -> ChangingMachineInfo {
-  let mut sys = sysinfo::System::new_all();
-  let mut machine = machine_info::Machine::new();
-  let mut partitions = sysinfo::Disks::new();
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SampleIntervals {
+  pub cpu: Duration,
+  pub ram: Duration,
+  pub disk: Duration,
+  pub network: Duration,
+  pub gpu: Duration,
+  pub sensors: Duration,
+}
 
-  ChangingMachineInfo {
-    cpu_usage: get_cpu_info(&mut sys),
-    gpu_usage: get_gpu_info(&mut machine),
-    ram_usage: get_ram_info(&mut sys),
-    disk_usage: get_disk_info(&mut partitions),
+impl Default for SampleIntervals {
+  fn default() -> Self {
+    Self {
+      cpu: Duration::from_secs(1),
+      ram: Duration::from_secs(1),
+      // capacity barely moves and listing disks is the slowest refresh
+      disk: Duration::from_secs(10),
+      network: Duration::from_secs(1),
+      gpu: Duration::from_secs(2),
+      sensors: Duration::from_secs(2),
+    }
   }
 }
 
-pub fn initiate_system_info_fetcher(app: &App) -> Result<(), String> {
-  let app_handle = app.handle().clone();
+impl SampleIntervals {
+  pub fn get(&self, metric: SystemMetric) -> Duration {
+    match metric {
+      SystemMetric::Cpu => self.cpu,
+      SystemMetric::Ram => self.ram,
+      SystemMetric::Disk => self.disk,
+      SystemMetric::Network => self.network,
+      SystemMetric::Gpu => self.gpu,
+      SystemMetric::Sensors => self.sensors,
+    }
+  }
 
-  let basic_machine_info = get_basic_machine_info();
+  pub fn set(&mut self, metric: SystemMetric, interval: Duration) {
+    match metric {
+      SystemMetric::Cpu => self.cpu = interval,
+      SystemMetric::Ram => self.ram = interval,
+      SystemMetric::Disk => self.disk = interval,
+      SystemMetric::Network => self.network = interval,
+      SystemMetric::Gpu => self.gpu = interval,
+      SystemMetric::Sensors => self.sensors = interval,
+    }
+  }
+}
 
-  app_handle
-    .emit("basicMachineInfo", basic_machine_info)
-    .unwrap_or_else(|e| {
-      eprintln!("Failed to emit basic machine info event: {}", e);
-    });
+// Keeps the sysinfo state alive between samples. CPU usage is computed from
+// the difference between two refreshes of the same `System`, and network
+// rates from the difference between two refreshes of the totals, so
+// recreating them every tick gives wrong numbers as well as being slow.
+pub struct SystemSampler {
+  sys: sysinfo::System,
+  disks: sysinfo::Disks,
+  networks: sysinfo::Networks,
+  // probed on the first GPU sample and kept, probing the GPUs is expensive
+  gpu_sources: Option<Vec<Box<dyn GpuSource>>>,
+  gpu: Vec<GpuInfo>,
+  components: sysinfo::Components,
+  sensors: SensorReadings,
+  disk_io: Option<DiskIoTracker>,
+  intervals: SampleIntervals,
+  cpu_refreshed_at: Instant,
+  network_rates: RateTracker,
+  network_settings: NetworkSettings,
+  refreshed_at: HashMap<SystemMetric, Instant>,
+}
+
+impl SystemSampler {
+  pub fn new(intervals: SampleIntervals) -> Self {
+    let mut sys = sysinfo::System::new();
+    // the first refresh is the baseline the next CPU sample is measured from
+    sys.refresh_cpu_usage();
+
+    Self {
+      sys,
+      disks: sysinfo::Disks::new(),
+      networks: sysinfo::Networks::new_with_refreshed_list(),
+      gpu_sources: None,
+      gpu: Vec::new(),
+      components: sysinfo::Components::new(),
+      sensors: SensorReadings::default(),
+      disk_io: None,
+      intervals,
+      cpu_refreshed_at: Instant::now(),
+      network_rates: RateTracker::default(),
+      network_settings: NetworkSettings::default(),
+      refreshed_at: HashMap::new(),
+    }
+  }
+
+  pub fn intervals(&self) -> &SampleIntervals {
+    &self.intervals
+  }
 
-  // println!(
-  //   "{}",
-  //   serde_json::to_string_pretty(&basic_machine_info).unwrap()
-  // );
+  pub fn set_interval(&mut self, metric: SystemMetric, interval: Duration) {
+    self.intervals.set(metric, interval);
+  }
 
-  let app_handle_clone1 = app_handle.clone();
+  // `now` is when the refresh counts as done for the intervals, so every
+  // metric refreshed on one tick shares it.
+  pub fn refresh(&mut self, metric: SystemMetric, now: Instant) {
+    match metric {
+      SystemMetric::Cpu => {
+        // sysinfo needs some time between two refreshes to compute the usage
+        let elapsed = self.cpu_refreshed_at.elapsed();
+        if elapsed < sysinfo::MINIMUM_CPU_UPDATE_INTERVAL {
+          std::thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL - elapsed);
+        }
+        self.sys.refresh_cpu_usage();
+        self.cpu_refreshed_at = Instant::now();
+      }
+      SystemMetric::Ram => self.sys.refresh_memory(),
+      // the list is refreshed too so plugged in drives show up
+      SystemMetric::Disk => {
+        self.disks.refresh_list();
+        if let Some(stats) = diskstats::read_diskstats() {
+          self
+            .disk_io
+            .get_or_insert_with(DiskIoTracker::default)
+            .update(stats);
+        }
+      }
+      SystemMetric::Network => {
+        self.networks.refresh_list();
+        self
+          .network_rates
+          .update(get_network_counters(&self.networks, &self.network_settings));
+      }
+      SystemMetric::Gpu => {
+        let sources = self.gpu_sources.get_or_insert_with(gpu::probe_sources);
+        self.gpu = gpu::read_sources(sources);
+      }
+      SystemMetric::Sensors => self.sensors = sensors::read_sensors(&mut self.components),
+    }
 
-  async_runtime::spawn(async move {
-    loop {
-      let changing_machine_info = get_changing_machine_info();
+    self.refreshed_at.insert(metric, now);
+  }
 
-      app_handle_clone1
-        .emit("changingMachineInfo", changing_machine_info.clone())
-        .unwrap_or_else(|e| {
-          eprintln!("Failed to emit changing machine info event: {}", e);
-        });
+  fn is_due(&self, metric: SystemMetric, now: Instant) -> bool {
+    self
+      .refreshed_at
+      .get(&metric)
+      .is_none_or(|at| now.saturating_duration_since(*at) >= self.intervals.get(metric))
+  }
 
-      // println!(
-      //   "{}",
-      //   serde_json::to_string_pretty(&changing_machine_info).unwrap()
-      // );
+  // Refreshes the requested metrics whose interval has passed and returns
+  // the ones that were refreshed.
+  pub fn refresh_due(
+    &mut self,
+    metrics: &HashSet<SystemMetric>,
+    now: Instant,
+  ) -> HashSet<SystemMetric> {
+    let due: HashSet<SystemMetric> = metrics
+      .iter()
+      .copied()
+      .filter(|metric| self.is_due(*metric, now))
+      .collect();
 
-      std::thread::sleep(std::time::Duration::from_secs(1));
+    for metric in &due {
+      self.refresh(*metric, now);
     }
-  });
 
-  let app_handle_clone2 = app_handle.clone();
+    due
+  }
+
+  fn sampled(&self, metrics: &HashSet<SystemMetric>, metric: SystemMetric) -> bool {
+    metrics.contains(&metric) && self.refreshed_at.contains_key(&metric)
+  }
+
+  // Reads the last sample of the requested metrics without refreshing
+  // anything; metrics that weren't requested or sampled yet are left empty.
+  pub fn machine_info(&self, metrics: &HashSet<SystemMetric>) -> ChangingMachineInfo {
+    ChangingMachineInfo {
+      cpu_usage: self
+        .sampled(metrics, SystemMetric::Cpu)
+        .then(|| get_cpu_info(&self.sys)),
+      gpu_usage: self
+        .sampled(metrics, SystemMetric::Gpu)
+        .then(|| self.gpu.clone()),
+      gpu_support: self
+        .gpu_sources
+        .as_deref()
+        .filter(|_| self.sampled(metrics, SystemMetric::Gpu))
+        .map(gpu::support),
+      ram_usage: self
+        .sampled(metrics, SystemMetric::Ram)
+        .then(|| get_ram_info(&self.sys)),
+      disk_usage: self
+        .sampled(metrics, SystemMetric::Disk)
+        .then(|| get_disk_info(&self.disks)),
+      disk_io: self
+        .disk_io
+        .as_ref()
+        .filter(|_| self.sampled(metrics, SystemMetric::Disk))
+        .map(|disk_io| disk_io.io().to_vec()),
+      sensors: self
+        .sampled(metrics, SystemMetric::Sensors)
+        .then(|| self.sensors.clone()),
+    }
+  }
+
+  pub fn set_network_settings(&mut self, settings: NetworkSettings) {
+    self.network_settings = settings;
+  }
+
+  pub fn network_info(&self) -> NetworkSummary {
+    NetworkSummary {
+      interfaces: get_network_info(&self.networks, self.network_rates.rates()),
+      total: self.network_rates.total(),
+    }
+  }
+
+  pub fn record_history(
+    &self,
+    history: &mut MetricHistory,
+    refreshed: &HashSet<SystemMetric>,
+    time: u64,
+  ) {
+    if refreshed.contains(&SystemMetric::Cpu) {
+      for (core, cpu) in self.sys.cpus().iter().enumerate() {
+        history.record(
+          MetricSeries::Cpu(core),
+          Point {
+            time,
+            value: cpu.cpu_usage() as f64,
+          },
+        );
+      }
+    }
+
+    if refreshed.contains(&SystemMetric::Ram) {
+      history.record(
+        MetricSeries::RamUsed,
+        Point {
+          time,
+          value: self.sys.used_memory() as f64,
+        },
+      );
+    }
+
+    if let (true, Some(disk_io)) = (refreshed.contains(&SystemMetric::Disk), &self.disk_io) {
+      let (read, written) = disk_io.total();
+      history.record(MetricSeries::DiskRead, Point { time, value: read });
+      history.record(
+        MetricSeries::DiskWritten,
+        Point {
+          time,
+          value: written,
+        },
+      );
+    }
+
+    if refreshed.contains(&SystemMetric::Network) {
+      let total = self.network_rates.total();
+      history.record(
+        MetricSeries::NetworkReceived,
+        Point {
+          time,
+          value: total.received_per_sec,
+        },
+      );
+      history.record(
+        MetricSeries::NetworkTransmitted,
+        Point {
+          time,
+          value: total.transmitted_per_sec,
+        },
+      );
+    }
+  }
+
+  pub fn sample(&self, subscriber: &str, metrics: &HashSet<SystemMetric>) -> SystemSample {
+    SystemSample {
+      subscriber: subscriber.to_string(),
+      machine: self.machine_info(metrics),
+      network: self
+        .sampled(metrics, SystemMetric::Network)
+        .then(|| self.network_info()),
+    }
+  }
+}
+
+// Handle to the running fetcher loop. Subscriptions can be changed while it
+// runs, and it exits on its next tick once stopped.
+#[derive(Debug, Clone, Default)]
+pub struct SystemFetcher {
+  subscriptions: Arc<Mutex<Subscriptions>>,
+  network_settings: Arc<Mutex<NetworkSettings>>,
+  stopped: Arc<AtomicBool>,
+}
+
+impl SystemFetcher {
+  pub fn subscribe(&self, subscriber: &str, window: &str, subscriptions: &[MetricSubscription]) {
+    self
+      .subscriptions
+      .lock()
+      .unwrap()
+      .subscribe(subscriber, window, subscriptions);
+  }
+
+  pub fn unsubscribe(&self, subscriber: &str) -> bool {
+    self.subscriptions.lock().unwrap().unsubscribe(subscriber)
+  }
+
+  pub fn retain(&self, keep: impl FnMut(&str) -> bool) {
+    self.subscriptions.lock().unwrap().retain(keep);
+  }
+
+  pub fn has_subscribers(&self) -> bool {
+    !self.subscriptions.lock().unwrap().is_empty()
+  }
 
-  async_runtime::spawn(async move {
-    let mut net = sysinfo::Networks::new_with_refreshed_list();
+  pub fn network_settings(&self) -> NetworkSettings {
+    self.network_settings.lock().unwrap().clone()
+  }
 
-    loop {
-      std::thread::sleep(std::time::Duration::from_secs(1));
+  pub fn set_network_settings(&self, settings: NetworkSettings) {
+    *self.network_settings.lock().unwrap() = settings;
+  }
 
-      let network_info = get_network_info(&mut net);
+  pub fn stop(&self) {
+    self.stopped.store(true, Ordering::Relaxed);
+  }
+
+  pub fn is_stopped(&self) -> bool {
+    self.stopped.load(Ordering::Relaxed)
+  }
+}
 
-      app_handle_clone2
-        .emit("networkInfo", network_info.clone())
-        .unwrap_or_else(|e| {
-          eprintln!("Failed to emit network info event: {}", e);
-        });
+// Milliseconds since the unix epoch, the time samples are recorded with.
+pub fn timestamp() -> u64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|duration| duration.as_millis() as u64)
+    .unwrap_or_default()
+}
+
+// The history outlives the fetcher so it is still there when the fetcher is
+// started again.
+pub fn initiate_system_info_fetcher<R: Runtime>(
+  app: &AppHandle<R>,
+  history: Arc<Mutex<MetricHistory>>,
+) -> Result<SystemFetcher, String> {
+  let app_handle = app.clone();
+  let fetcher = SystemFetcher::default();
+
+  let basic_machine_info = get_basic_machine_info();
+
+  app_handle
+    .emit("basicMachineInfo", basic_machine_info)
+    .unwrap_or_else(|e| {
+      eprintln!("Failed to emit basic machine info event: {}", e);
+    });
 
-      // println!("{}", serde_json::to_string_pretty(&network_info).unwrap());
+  let fetcher_clone = fetcher.clone();
+
+  // the loop blocks while sampling and sleeping, so it gets its own thread
+  // instead of holding a worker of the async runtime
+  std::thread::spawn(move || {
+    let mut sampler = SystemSampler::new(SampleIntervals::default());
+
+    while !fetcher_clone.is_stopped() {
+      let now = Instant::now();
+      let (due, intervals) = {
+        let mut subscriptions = fetcher_clone.subscriptions.lock().unwrap();
+        (subscriptions.take_due(now), subscriptions.intervals())
+      };
+
+      // metrics nobody is subscribed to anymore are simply never refreshed
+      for (metric, interval) in intervals {
+        sampler.set_interval(metric, interval);
+      }
+      sampler.set_network_settings(fetcher_clone.network_settings());
+      let metrics: HashSet<SystemMetric> = due
+        .iter()
+        .flat_map(|due| due.metrics.iter().copied())
+        .collect();
+      let refreshed = sampler.refresh_due(&metrics, now);
+      sampler.record_history(&mut history.lock().unwrap(), &refreshed, timestamp());
+
+      for due in due {
+        app_handle
+          .emit_to(
+            due.window.as_str(),
+            "systemMetrics",
+            sampler.sample(&due.subscriber, &due.metrics),
+          )
+          .unwrap_or_else(|e| {
+            eprintln!("Failed to emit system metrics event: {}", e);
+          });
+      }
+
+      std::thread::sleep(MIN_SUBSCRIPTION_INTERVAL);
     }
   });
 
-  Ok(())
+  Ok(fetcher)
 }
 
 // pub fn get_cpu_usage() {
@@ -300,32 +671,12 @@ pub fn initiate_system_info_fetcher(app: &App) -> Result<(), String> {
 //   );
 // }
 
-// pub fn get_temperature() {
-// let plat: systemstat::platform::PlatformImpl = systemstat::Platform::new();
-// let temp = plat
-//   .cpu_temp()
-//   .unwrap_or_else(|e| panic!("Failed to get CPU temperature: {}", e));
-
-// println!("CPU Temperature: {}°C", temp);
-
-//   let components = sysinfo::Components::new_with_refreshed_list();
-
-//   if components.is_empty() {
-//     println!("There is no temperature information available.");
-//   } else {
-//     for component in &components {
-//       println!("{}: {}°C", component.label(), component.temperature());
-//     }
-//   }
-// }
-
 // Information required to get system information
-// - CPU load, temperature, clock speed and fan speed
-// - GPU load, temperature, clock speed and fan speed
+// - CPU load, clock speed
+// - GPU load, clock speed
 // - RAM usage
 // - Network upload and download speed
 // - Disk usage
-// - Top processes
 // Use the following crates to get the information:
 // - sysinfo
 // - machine-info
@@ -408,10 +759,3 @@ pub fn initiate_system_info_fetcher(app: &App) -> Result<(), String> {
 //   sys.refresh_all();
 //   println!("CPU Usage: {}%", sys.global_cpu_usage());
 // }
-
-// pub fn get_temperature() {
-//   let components = sysinfo::Components::new_with_refreshed_list();
-//   for component in &components {
-//     println!("{}: {}°C", component.label(), component.temperature());
-//   }
-// }
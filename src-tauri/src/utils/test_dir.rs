@@ -0,0 +1,20 @@
+use std::{
+  fs,
+  path::{Path, PathBuf},
+};
+
+// A fresh directory under the system temp dir. Tests run in parallel, so each
+// one passes its own name; the pid keeps separate runs apart.
+pub fn temp_dir(name: &str) -> PathBuf {
+  let dir = std::env::temp_dir().join(format!("miyabi-{}-{}", name, std::process::id()));
+  let _ = fs::remove_dir_all(&dir);
+  fs::create_dir_all(&dir).unwrap();
+  dir
+}
+
+// Writes `file` into `dir`, creating the directory first, like a fake sysfs
+// attribute.
+pub fn write(dir: &Path, file: &str, contents: &str) {
+  fs::create_dir_all(dir).unwrap();
+  fs::write(dir.join(file), contents).unwrap();
+}
@@ -0,0 +1,472 @@
+use std::sync::Mutex;
+
+use tauri::{
+  menu::{CheckMenuItem, IsMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu},
+  tray::TrayIconBuilder,
+  AppHandle, Manager, Runtime,
+};
+use tauri_plugin_shell::ShellExt;
+
+use super::{
+  error::WidgetError, profile::ProfileStore, settings::Settings, widget::Widget,
+  widget_handler::WidgetHandler,
+};
+
+const TRAY_ID: &str = "widgets";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrayAction {
+  ToggleWidget(String),
+  SwitchProfile(Option<String>),
+  LockAll,
+  ClickThrough,
+  Reload,
+  OpenConfigFolder,
+  Quit,
+}
+
+impl TrayAction {
+  pub fn menu_id(&self) -> String {
+    match self {
+      TrayAction::ToggleWidget(widget_id) => format!("widget:{}", widget_id),
+      TrayAction::SwitchProfile(Some(name)) => format!("profile:{}", name),
+      TrayAction::SwitchProfile(None) => "profile".to_string(),
+      TrayAction::LockAll => "lock_all".to_string(),
+      TrayAction::ClickThrough => "click_through".to_string(),
+      TrayAction::Reload => "reload".to_string(),
+      TrayAction::OpenConfigFolder => "open_config".to_string(),
+      TrayAction::Quit => "quit".to_string(),
+    }
+  }
+
+  pub fn from_menu_id(id: &str) -> Option<Self> {
+    if let Some(widget_id) = id.strip_prefix("widget:") {
+      return Some(TrayAction::ToggleWidget(widget_id.to_string()));
+    }
+    if let Some(name) = id.strip_prefix("profile:") {
+      return Some(TrayAction::SwitchProfile(Some(name.to_string())));
+    }
+
+    match id {
+      "profile" => Some(TrayAction::SwitchProfile(None)),
+      "lock_all" => Some(TrayAction::LockAll),
+      "click_through" => Some(TrayAction::ClickThrough),
+      "reload" => Some(TrayAction::Reload),
+      "open_config" => Some(TrayAction::OpenConfigFolder),
+      "quit" => Some(TrayAction::Quit),
+      _ => None,
+    }
+  }
+}
+
+// A plain description of the tray menu, kept apart from the OS menu so it can
+// be built and compared without a running app.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrayItem {
+  Check {
+    action: TrayAction,
+    label: String,
+    checked: bool,
+    enabled: bool,
+  },
+  Action {
+    action: TrayAction,
+    label: String,
+  },
+  Submenu {
+    label: String,
+    items: Vec<TrayItem>,
+  },
+  Separator,
+}
+
+// Every top-level widget gets a show/hide toggle. Widgets without a window,
+// either because the active profile leaves them out or because their type has
+// no page yet, are listed but can't be toggled.
+pub fn tray_items(
+  widgets: &[Widget],
+  effective: &[Widget],
+  profiles: &ProfileStore,
+  settings: &Settings,
+) -> Vec<TrayItem> {
+  let mut items: Vec<TrayItem> = widgets
+    .iter()
+    .map(|widget| {
+      let open = effective
+        .iter()
+        .find(|effective| effective.id == widget.id)
+        .filter(|effective| effective.widget_type.route().is_some());
+
+      TrayItem::Check {
+        action: TrayAction::ToggleWidget(widget.id.clone()),
+        label: widget.window_params().title,
+        checked: open.is_some_and(|widget| !widget.property.hidden.unwrap_or(false)),
+        enabled: open.is_some(),
+      }
+    })
+    .collect();
+
+  if !items.is_empty() {
+    items.push(TrayItem::Separator);
+  }
+
+  if !profiles.profiles.is_empty() {
+    let mut profile_items = vec![TrayItem::Check {
+      action: TrayAction::SwitchProfile(None),
+      label: "All widgets".to_string(),
+      checked: profiles.active.is_none(),
+      enabled: true,
+    }];
+
+    profile_items.extend(profiles.profiles.keys().map(|name| TrayItem::Check {
+      action: TrayAction::SwitchProfile(Some(name.clone())),
+      label: name.clone(),
+      checked: profiles.active.as_deref() == Some(name.as_str()),
+      enabled: true,
+    }));
+
+    items.push(TrayItem::Submenu {
+      label: "Profiles".to_string(),
+      items: profile_items,
+    });
+  }
+
+  items.extend([
+    TrayItem::Check {
+      action: TrayAction::LockAll,
+      label: "Lock all positions".to_string(),
+      checked: settings.locked(),
+      enabled: true,
+    },
+    TrayItem::Check {
+      action: TrayAction::ClickThrough,
+      label: "Click-through".to_string(),
+      checked: settings.click_through(),
+      enabled: true,
+    },
+    TrayItem::Action {
+      action: TrayAction::Reload,
+      label: "Reload configs".to_string(),
+    },
+    TrayItem::Action {
+      action: TrayAction::OpenConfigFolder,
+      label: "Open config folder".to_string(),
+    },
+    TrayItem::Separator,
+    TrayItem::Action {
+      action: TrayAction::Quit,
+      label: "Quit".to_string(),
+    },
+  ]);
+
+  items
+}
+
+fn build_item<R: Runtime>(
+  app: &AppHandle<R>,
+  item: &TrayItem,
+) -> tauri::Result<Box<dyn IsMenuItem<R>>> {
+  Ok(match item {
+    TrayItem::Check {
+      action,
+      label,
+      checked,
+      enabled,
+    } => Box::new(CheckMenuItem::with_id(
+      app,
+      action.menu_id(),
+      label,
+      *enabled,
+      *checked,
+      None::<&str>,
+    )?),
+    TrayItem::Action { action, label } => Box::new(MenuItem::with_id(
+      app,
+      action.menu_id(),
+      label,
+      true,
+      None::<&str>,
+    )?),
+    TrayItem::Submenu { label, items } => {
+      let submenu = Submenu::new(app, label, true)?;
+      for item in items {
+        submenu.append(build_item(app, item)?.as_ref())?;
+      }
+      Box::new(submenu)
+    }
+    TrayItem::Separator => Box::new(PredefinedMenuItem::separator(app)?),
+  })
+}
+
+fn build_menu<R: Runtime>(app: &AppHandle<R>, items: &[TrayItem]) -> tauri::Result<Menu<R>> {
+  let menu = Menu::new(app)?;
+  for item in items {
+    menu.append(build_item(app, item)?.as_ref())?;
+  }
+
+  Ok(menu)
+}
+
+pub fn create_tray<R: Runtime>(app: &AppHandle<R>, items: &[TrayItem]) -> tauri::Result<()> {
+  let mut tray = TrayIconBuilder::with_id(TRAY_ID)
+    .menu(&build_menu(app, items)?)
+    .tooltip("Miyabi Widgets")
+    .on_menu_event(handle_menu_event);
+
+  if let Some(icon) = app.default_window_icon() {
+    tray = tray.icon(icon.clone());
+  }
+
+  tray.build(app)?;
+
+  Ok(())
+}
+
+// The menu is rebuilt as a whole, which is cheap and keeps the check marks in
+// line with the handler after every change.
+pub fn update_tray<R: Runtime>(app: &AppHandle<R>, items: &[TrayItem]) -> tauri::Result<()> {
+  let Some(tray) = app.tray_by_id(TRAY_ID) else {
+    return Ok(());
+  };
+
+  tray.set_menu(Some(build_menu(app, items)?))
+}
+
+fn handle_menu_event<R: Runtime>(app: &AppHandle<R>, event: MenuEvent) {
+  let Some(action) = TrayAction::from_menu_id(event.id().as_ref()) else {
+    return;
+  };
+
+  if action == TrayAction::Quit {
+    app.exit(0);
+    return;
+  }
+
+  // switching profiles and reloading wait for windows to close, which can't
+  // happen on the main thread the menu events arrive on
+  let app = app.clone();
+  std::thread::spawn(move || {
+    run_action(&app, &action).unwrap_or_else(|e| {
+      eprintln!("Failed to run tray action {}: {}", action.menu_id(), e);
+    });
+  });
+}
+
+fn run_action<R: Runtime>(app: &AppHandle<R>, action: &TrayAction) -> Result<(), WidgetError> {
+  let state = app.state::<Mutex<WidgetHandler>>();
+  let mut widget_handler = state.lock().unwrap();
+
+  let result = match action {
+    TrayAction::ToggleWidget(widget_id) => match widget_handler.get_widget(widget_id) {
+      Some(widget) => {
+        let hidden = widget.property.hidden.unwrap_or(false);
+        widget_handler.set_widget_hidden(app, widget_id, !hidden)
+      }
+      None => Err(WidgetError::NotFound(widget_id.clone())),
+    },
+    TrayAction::SwitchProfile(name) => widget_handler.switch_profile(app, name.clone()),
+    TrayAction::LockAll => {
+      let locked = !widget_handler.settings.locked();
+      widget_handler.set_locked(app, locked)
+    }
+    TrayAction::ClickThrough => {
+      let click_through = !widget_handler.settings.click_through();
+      widget_handler.set_click_through(app, click_through)
+    }
+    TrayAction::Reload => widget_handler.reload(app),
+    TrayAction::OpenConfigFolder => app
+      .shell()
+      .open(widget_handler.config_dir.to_string_lossy(), None)
+      .map_err(|e| WidgetError::Io(format!("failed to open config folder: {}", e))),
+    TrayAction::Quit => Ok(()),
+  };
+
+  // check items toggle themselves when clicked, so put them back in line
+  // with the handler even if the action failed
+  widget_handler.sync_tray(app);
+
+  result
+}
+
+#[cfg(test)]
+mod tests {
+  use serde_json::json;
+
+  use super::*;
+  use crate::utils::profile::Profile;
+
+  fn widget(id: &str, widget_type: &str, property: serde_json::Value) -> Widget {
+    serde_json::from_value(json!({
+      "id": id,
+      "description": "",
+      "widget_type": widget_type,
+      "property": property,
+      "appearance": {},
+    }))
+    .unwrap()
+  }
+
+  // The label, checked and enabled state of the check item for `action`,
+  // looking into submenus too.
+  fn find_check(items: &[TrayItem], action: &TrayAction) -> Option<(String, bool, bool)> {
+    items.iter().find_map(|item| match item {
+      TrayItem::Check {
+        action: item_action,
+        label,
+        checked,
+        enabled,
+      } if item_action == action => Some((label.clone(), *checked, *enabled)),
+      TrayItem::Submenu { items, .. } => find_check(items, action),
+      _ => None,
+    })
+  }
+
+  fn check(items: &[TrayItem], action: &TrayAction) -> (String, bool, bool) {
+    find_check(items, action).unwrap()
+  }
+
+  fn toggle(widget_id: &str) -> TrayAction {
+    TrayAction::ToggleWidget(widget_id.to_string())
+  }
+
+  #[test]
+  fn tray_items_widget_toggles() {
+    let widgets = [
+      widget("cpu", "DefaultSystemMonitor", json!({ "title": "CPU" })),
+      widget(
+        "media",
+        "DefaultMediaPlayerControls",
+        json!({ "hidden": true }),
+      ),
+      widget("group", "Container", json!({})),
+      widget("clock", "DefaultDateTime", json!({})),
+    ];
+    // the active profile leaves out the container
+    let effective = [widgets[0].clone(), widgets[1].clone(), widgets[3].clone()];
+
+    let items = tray_items(
+      &widgets,
+      &effective,
+      &ProfileStore::default(),
+      &Settings::default(),
+    );
+
+    assert_eq!(
+      check(&items, &toggle("cpu")),
+      ("CPU".to_string(), true, true)
+    );
+    assert_eq!(
+      check(&items, &toggle("media")),
+      ("media".to_string(), false, true)
+    );
+    assert_eq!(
+      check(&items, &toggle("group")),
+      ("group".to_string(), false, false)
+    );
+    // no page to open it in yet
+    assert_eq!(
+      check(&items, &toggle("clock")),
+      ("clock".to_string(), false, false)
+    );
+    assert_eq!(items[4], TrayItem::Separator);
+  }
+
+  #[test]
+  fn tray_items_lock_and_click_through_states() {
+    let unset = tray_items(&[], &[], &ProfileStore::default(), &Settings::default());
+    assert!(!check(&unset, &TrayAction::LockAll).1);
+    assert!(!check(&unset, &TrayAction::ClickThrough).1);
+    // without widgets the menu starts with the toggles
+    assert!(matches!(
+      unset[0],
+      TrayItem::Check {
+        action: TrayAction::LockAll,
+        ..
+      }
+    ));
+
+    let settings = Settings {
+      locked: Some(true),
+      click_through: Some(true),
+      ..Settings::default()
+    };
+    let set = tray_items(&[], &[], &ProfileStore::default(), &settings);
+    assert!(check(&set, &TrayAction::LockAll).1);
+    assert!(check(&set, &TrayAction::ClickThrough).1);
+  }
+
+  #[test]
+  fn tray_items_without_profiles() {
+    let items = tray_items(&[], &[], &ProfileStore::default(), &Settings::default());
+
+    assert!(!items
+      .iter()
+      .any(|item| matches!(item, TrayItem::Submenu { .. })));
+  }
+
+  #[test]
+  fn tray_items_profile_entries() {
+    let profiles = ProfileStore {
+      active: Some("work".to_string()),
+      profiles: [
+        ("work".to_string(), Profile::default()),
+        ("gaming".to_string(), Profile::default()),
+      ]
+      .into_iter()
+      .collect(),
+    };
+
+    let items = tray_items(&[], &[], &profiles, &Settings::default());
+    let Some(TrayItem::Submenu { label, items }) = items
+      .iter()
+      .find(|item| matches!(item, TrayItem::Submenu { .. }))
+    else {
+      panic!("no profiles submenu");
+    };
+
+    assert_eq!(label, "Profiles");
+    let entries: Vec<(String, bool)> = items
+      .iter()
+      .map(|item| match item {
+        TrayItem::Check { label, checked, .. } => (label.clone(), *checked),
+        _ => panic!("unexpected profile entry {:?}", item),
+      })
+      .collect();
+    assert_eq!(
+      entries,
+      [
+        ("All widgets".to_string(), false),
+        ("gaming".to_string(), false),
+        ("work".to_string(), true),
+      ]
+    );
+
+    let all = tray_items(
+      &[],
+      &[],
+      &ProfileStore {
+        active: None,
+        ..profiles
+      },
+      &Settings::default(),
+    );
+    assert!(check(&all, &TrayAction::SwitchProfile(None)).1);
+  }
+
+  #[test]
+  fn tray_action_menu_ids_round_trip() {
+    for action in [
+      toggle("clock"),
+      TrayAction::SwitchProfile(Some("work".to_string())),
+      TrayAction::SwitchProfile(None),
+      TrayAction::LockAll,
+      TrayAction::ClickThrough,
+      TrayAction::Reload,
+      TrayAction::OpenConfigFolder,
+      TrayAction::Quit,
+    ] {
+      assert_eq!(TrayAction::from_menu_id(&action.menu_id()), Some(action));
+    }
+    assert_eq!(TrayAction::from_menu_id("unknown"), None);
+  }
+}
@@ -1,19 +1,46 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
+use super::{processes::ProcessSort, rules::AutoHideRule};
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum DefaultOrientation {
   Horizontal,
   Vertical,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub enum WidgetLayer {
+  #[default]
+  Desktop,
+  Normal,
+  Top,
+  // stays on the desktop until a fullscreen app is focused, then floats above it
+  Overlay,
+}
+
+impl WidgetLayer {
+  // (always on bottom, always on top)
+  pub fn window_levels(&self, fullscreen_app: bool) -> (bool, bool) {
+    match self {
+      WidgetLayer::Desktop => (true, false),
+      WidgetLayer::Normal => (false, false),
+      WidgetLayer::Top => (false, true),
+      WidgetLayer::Overlay => (!fullscreen_app, fullscreen_app),
+    }
+  }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum WidgetTheme {
   Normal,
   Dynamic,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 pub struct WidgetAppearance {
+  pub palette: Option<String>,
   pub theme: Option<WidgetTheme>,
   pub background_color: Option<String>,
   pub opacity: Option<f32>,
@@ -24,6 +51,107 @@ pub struct WidgetAppearance {
   pub fontscale: Option<f32>,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ThemeConfig {
+  pub defaults: Option<WidgetAppearance>,
+  pub palettes: Option<HashMap<String, WidgetAppearance>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ResolvedAppearance {
+  pub theme: WidgetTheme,
+  pub background_color: String,
+  pub opacity: f32,
+  pub border_size: u32,
+  pub border_color: String,
+  pub border_radius: u32,
+  pub padding: u32,
+  pub fontscale: f32,
+}
+
+impl Default for ResolvedAppearance {
+  fn default() -> Self {
+    Self {
+      theme: WidgetTheme::Normal,
+      background_color: "rgba(15, 23, 42, 0.6)".to_string(),
+      opacity: 1.0,
+      border_size: 0,
+      border_color: "transparent".to_string(),
+      border_radius: 0,
+      padding: 16,
+      fontscale: 1.0,
+    }
+  }
+}
+
+impl WidgetAppearance {
+  // Fields set on `self` win, anything left unset is taken from `base`.
+  pub fn overlay(&self, base: &WidgetAppearance) -> WidgetAppearance {
+    WidgetAppearance {
+      palette: self.palette.clone().or_else(|| base.palette.clone()),
+      theme: self.theme.clone().or_else(|| base.theme.clone()),
+      background_color: self
+        .background_color
+        .clone()
+        .or_else(|| base.background_color.clone()),
+      opacity: self.opacity.or(base.opacity),
+      border_size: self.border_size.or(base.border_size),
+      border_color: self
+        .border_color
+        .clone()
+        .or_else(|| base.border_color.clone()),
+      border_radius: self.border_radius.or(base.border_radius),
+      padding: self.padding.or(base.padding),
+      fontscale: self.fontscale.or(base.fontscale),
+    }
+  }
+
+  pub fn resolve(&self) -> ResolvedAppearance {
+    let defaults = ResolvedAppearance::default();
+
+    ResolvedAppearance {
+      theme: self.theme.clone().unwrap_or(defaults.theme),
+      background_color: self
+        .background_color
+        .clone()
+        .unwrap_or(defaults.background_color),
+      opacity: self.opacity.unwrap_or(defaults.opacity).clamp(0.0, 1.0),
+      border_size: self.border_size.unwrap_or(defaults.border_size),
+      border_color: self.border_color.clone().unwrap_or(defaults.border_color),
+      border_radius: self.border_radius.unwrap_or(defaults.border_radius),
+      padding: self.padding.unwrap_or(defaults.padding),
+      fontscale: self.fontscale.unwrap_or(defaults.fontscale),
+    }
+  }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Anchor {
+  TopLeft,
+  Top,
+  TopRight,
+  Left,
+  Center,
+  Right,
+  BottomLeft,
+  Bottom,
+  BottomRight,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum MonitorSelector {
+  Index(usize),
+  Name(String),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct WidgetPlacement {
+  pub monitor: Option<MonitorSelector>,
+  pub anchor: Anchor,
+  pub offset: Option<(i32, i32)>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct WidgetProperty {
   pub title: Option<String>,
@@ -31,20 +159,91 @@ pub struct WidgetProperty {
   pub hidden: Option<bool>,
   pub resizable: Option<bool>,
   pub draggable: Option<bool>,
-  pub position: Option<(u32, u32)>,
+  pub position: Option<(i32, i32)>,
+  pub placement: Option<WidgetPlacement>,
   pub size: Option<(u32, u32)>,
   pub orientation: Option<DefaultOrientation>,
+  pub click_through: Option<bool>,
+  pub locked: Option<bool>,
+  pub layer: Option<WidgetLayer>,
+  pub auto_hide: Option<Vec<AutoHideRule>>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum WidgetType {
   DefaultDateTime,
   DefaultWeather,
   DefaultMediaPlayerControls,
   DefaultAppLauncher,
+  DefaultSystemMonitor,
+  Container,
   Custom,
 }
 
+impl WidgetType {
+  pub fn route(&self) -> Option<&'static str> {
+    match self {
+      WidgetType::DefaultMediaPlayerControls => Some("/media"),
+      WidgetType::DefaultSystemMonitor => Some("/system"),
+      WidgetType::Container => Some("/container"),
+      _ => None,
+    }
+  }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SystemMetric {
+  Cpu,
+  Ram,
+  Disk,
+  Network,
+  Gpu,
+  // temperatures and fan speeds
+  Sensors,
+}
+
+impl SystemMetric {
+  pub const ALL: [SystemMetric; 6] = [
+    SystemMetric::Cpu,
+    SystemMetric::Ram,
+    SystemMetric::Disk,
+    SystemMetric::Network,
+    SystemMetric::Gpu,
+    SystemMetric::Sensors,
+  ];
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum LayoutDirection {
+  Row,
+  Column,
+  Grid,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum LayoutAlign {
+  Start,
+  Center,
+  End,
+  Stretch,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct WidgetLayout {
+  pub direction: Option<LayoutDirection>,
+  pub columns: Option<u32>,
+  pub gap: Option<u32>,
+  pub align: Option<LayoutAlign>,
+  pub justify: Option<LayoutAlign>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ProcessList {
+  pub sort: Option<ProcessSort>,
+  pub count: Option<usize>,
+  pub interval_ms: Option<u64>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Widget {
   pub id: String,
@@ -52,5 +251,381 @@ pub struct Widget {
   pub widget_type: WidgetType,
   pub property: WidgetProperty,
   pub appearance: WidgetAppearance,
+  pub layout: Option<WidgetLayout>,
+  // which metrics a system monitor shows, all of them when unset
+  pub metrics: Option<Vec<SystemMetric>>,
+  // how often each metric is sent to a system monitor, in milliseconds
+  pub metric_intervals: Option<HashMap<SystemMetric, u64>>,
+  // shows the top processes under a system monitor when set
+  pub processes: Option<ProcessList>,
   pub children: Option<Vec<Widget>>,
 }
+
+impl DefaultOrientation {
+  pub fn default_size(&self) -> (f64, f64) {
+    match self {
+      DefaultOrientation::Horizontal => (640.0, 160.0),
+      DefaultOrientation::Vertical => (320.0, 480.0),
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowParams {
+  pub title: String,
+  pub icon: Option<String>,
+  pub visible: bool,
+  pub resizable: bool,
+  pub draggable: bool,
+  pub click_through: bool,
+  pub layer: WidgetLayer,
+  pub auto_hide: Vec<AutoHideRule>,
+  pub position: Option<(f64, f64)>,
+  pub size: (f64, f64),
+}
+
+impl Widget {
+  pub fn find(&self, widget_id: &str) -> Option<&Widget> {
+    if self.id == widget_id {
+      return Some(self);
+    }
+
+    self
+      .children
+      .iter()
+      .flatten()
+      .find_map(|child| child.find(widget_id))
+  }
+
+  pub fn contains_type(&self, widget_type: &WidgetType) -> bool {
+    self.widget_type == *widget_type
+      || self
+        .children
+        .iter()
+        .flatten()
+        .any(|child| child.contains_type(widget_type))
+  }
+
+  // Size precedence: an explicit `size` wins, then the `orientation` preset,
+  // then the vertical preset every widget falls back to.
+  pub fn window_params(&self) -> WindowParams {
+    let property = &self.property;
+
+    let size = match (property.size, &property.orientation) {
+      (Some((width, height)), _) => (width as f64, height as f64),
+      (None, Some(orientation)) => orientation.default_size(),
+      (None, None) => DefaultOrientation::Vertical.default_size(),
+    };
+
+    // a locked widget can neither be moved nor resized
+    let locked = property.locked.unwrap_or(false);
+
+    WindowParams {
+      title: property.title.clone().unwrap_or_else(|| self.id.clone()),
+      icon: property.icon.clone(),
+      visible: !property.hidden.unwrap_or(false),
+      resizable: property.resizable.unwrap_or(false) && !locked,
+      draggable: property.draggable.unwrap_or(true) && !locked,
+      click_through: property.click_through.unwrap_or(false),
+      layer: property.layer.unwrap_or_default(),
+      auto_hide: property.auto_hide.clone().unwrap_or_default(),
+      position: property.position.map(|(x, y)| (x as f64, y as f64)),
+      size,
+    }
+  }
+}
+
+// Layers the global defaults, then the palette the widget (or the defaults)
+// refer to, then the widget's own fields. Unknown palettes are skipped.
+pub fn merge_appearance(theme: &ThemeConfig, appearance: &WidgetAppearance) -> WidgetAppearance {
+  let defaults = theme.defaults.clone().unwrap_or_default();
+
+  let palette = appearance
+    .palette
+    .as_ref()
+    .or(defaults.palette.as_ref())
+    .and_then(|name| theme.palettes.as_ref()?.get(name));
+
+  match palette {
+    Some(palette) => appearance.overlay(&palette.overlay(&defaults)),
+    None => appearance.overlay(&defaults),
+  }
+}
+
+pub fn find_widget<'a>(widgets: &'a [Widget], widget_id: &str) -> Option<&'a Widget> {
+  widgets.iter().find_map(|widget| widget.find(widget_id))
+}
+
+fn collect_ids<'a>(widgets: &'a [Widget], ids: &mut Vec<&'a str>) {
+  for widget in widgets {
+    ids.push(&widget.id);
+    collect_ids(widget.children.as_deref().unwrap_or_default(), ids);
+  }
+}
+
+// Children are looked up by id from their container's webview, so ids have to
+// be unique across the whole tree and not only among siblings.
+pub fn validate_widget_tree(widgets: &[Widget]) -> Result<(), String> {
+  let mut ids = Vec::new();
+  collect_ids(widgets, &mut ids);
+  ids.sort_unstable();
+
+  let mut duplicates: Vec<&str> = ids
+    .windows(2)
+    .filter(|pair| pair[0] == pair[1])
+    .map(|pair| pair[0])
+    .collect();
+  duplicates.dedup();
+
+  if duplicates.is_empty() {
+    Ok(())
+  } else {
+    Err(format!("duplicate widget ids: {}", duplicates.join(", ")))
+  }
+}
+
+// Ids end up as file names and window labels, so keep them to a safe charset.
+pub fn validate_widget_id(widget_id: &str) -> Result<(), String> {
+  if widget_id.is_empty() {
+    return Err("widget id can't be empty".to_string());
+  }
+
+  if !widget_id
+    .chars()
+    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+  {
+    return Err(format!(
+      "widget id {} may only contain letters, digits, '-' and '_'",
+      widget_id
+    ));
+  }
+
+  Ok(())
+}
+
+pub fn widget_ids(widgets: &[Widget]) -> Vec<String> {
+  let mut ids = Vec::new();
+  collect_ids(widgets, &mut ids);
+  ids.into_iter().map(|id| id.to_string()).collect()
+}
+
+pub fn unique_widget_id(base: &str, taken: &[String]) -> String {
+  let candidate = format!("{}-copy", base);
+  if !taken.contains(&candidate) {
+    return candidate;
+  }
+
+  (2..)
+    .map(|n| format!("{}-copy-{}", base, n))
+    .find(|candidate| !taken.contains(candidate))
+    .unwrap()
+}
+
+// Gives every child of a duplicated widget a fresh id, registering each new
+// id in `taken` so siblings can't collide with each other either.
+pub fn reassign_child_ids(widget: &mut Widget, taken: &mut Vec<String>) {
+  for child in widget.children.iter_mut().flatten() {
+    child.id = unique_widget_id(&child.id, taken);
+    taken.push(child.id.clone());
+    reassign_child_ids(child, taken);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use serde_json::json;
+
+  use super::*;
+
+  fn widget(property: serde_json::Value) -> Widget {
+    serde_json::from_value(json!({
+      "id": "clock",
+      "description": "",
+      "widget_type": "DefaultDateTime",
+      "property": property,
+      "appearance": {},
+    }))
+    .unwrap()
+  }
+
+  #[test]
+  fn window_params_defaults() {
+    let params = widget(json!({})).window_params();
+
+    assert_eq!(params.title, "clock");
+    assert_eq!(params.icon, None);
+    assert!(params.visible);
+    assert!(!params.resizable);
+    assert!(params.draggable);
+    assert!(!params.click_through);
+    assert_eq!(params.layer, WidgetLayer::Desktop);
+    assert_eq!(params.position, None);
+    assert_eq!(params.size, DefaultOrientation::Vertical.default_size());
+  }
+
+  #[test]
+  fn window_params_size_wins_over_orientation() {
+    let sized = widget(json!({ "size": [200, 100], "orientation": "Horizontal" }));
+    assert_eq!(sized.window_params().size, (200.0, 100.0));
+
+    let oriented = widget(json!({ "orientation": "Horizontal" }));
+    assert_eq!(
+      oriented.window_params().size,
+      DefaultOrientation::Horizontal.default_size()
+    );
+  }
+
+  #[test]
+  fn window_params_title_and_visibility() {
+    let params =
+      widget(json!({ "title": "Clock", "icon": "clock.png", "hidden": true })).window_params();
+
+    assert_eq!(params.title, "Clock");
+    assert_eq!(params.icon.as_deref(), Some("clock.png"));
+    assert!(!params.visible);
+  }
+
+  #[test]
+  fn window_params_locked_overrides_draggable_and_resizable() {
+    let unlocked = widget(json!({ "resizable": true, "draggable": true })).window_params();
+    assert!(unlocked.resizable);
+    assert!(unlocked.draggable);
+
+    let locked =
+      widget(json!({ "resizable": true, "draggable": true, "locked": true })).window_params();
+    assert!(!locked.resizable);
+    assert!(!locked.draggable);
+
+    let pinned = widget(json!({ "draggable": false })).window_params();
+    assert!(!pinned.draggable);
+  }
+
+  #[test]
+  fn window_params_position() {
+    let params = widget(json!({ "position": [10, -20] })).window_params();
+    assert_eq!(params.position, Some((10.0, -20.0)));
+  }
+
+  fn theme() -> ThemeConfig {
+    serde_json::from_value(json!({
+      "defaults": { "background_color": "black", "opacity": 0.8, "padding": 8 },
+      "palettes": {
+        "light": { "background_color": "white", "border_color": "gray" },
+        "dark": { "background_color": "navy", "opacity": 0.5 },
+      },
+    }))
+    .unwrap()
+  }
+
+  fn appearance(value: serde_json::Value) -> WidgetAppearance {
+    serde_json::from_value(value).unwrap()
+  }
+
+  #[test]
+  fn merge_appearance_layers_defaults_palette_and_widget() {
+    let merged = merge_appearance(
+      &theme(),
+      &appearance(json!({ "palette": "light", "border_color": "red" })),
+    );
+
+    // the widget wins over the palette, the palette over the defaults
+    assert_eq!(merged.border_color.as_deref(), Some("red"));
+    assert_eq!(merged.background_color.as_deref(), Some("white"));
+    assert_eq!(merged.opacity, Some(0.8));
+    assert_eq!(merged.padding, Some(8));
+    assert_eq!(merged.palette.as_deref(), Some("light"));
+  }
+
+  #[test]
+  fn merge_appearance_skips_unknown_palette() {
+    let merged = merge_appearance(&theme(), &appearance(json!({ "palette": "missing" })));
+
+    assert_eq!(merged.background_color.as_deref(), Some("black"));
+    assert_eq!(merged.border_color, None);
+  }
+
+  #[test]
+  fn merge_appearance_uses_palette_from_defaults() {
+    let mut theme = theme();
+    theme.defaults.as_mut().unwrap().palette = Some("dark".to_string());
+
+    let merged = merge_appearance(&theme, &WidgetAppearance::default());
+    assert_eq!(merged.background_color.as_deref(), Some("navy"));
+    assert_eq!(merged.opacity, Some(0.5));
+    assert_eq!(merged.padding, Some(8));
+
+    // a widget's own palette replaces the default one
+    let merged = merge_appearance(&theme, &appearance(json!({ "palette": "light" })));
+    assert_eq!(merged.background_color.as_deref(), Some("white"));
+    assert_eq!(merged.opacity, Some(0.8));
+  }
+
+  #[test]
+  fn merge_appearance_without_theme() {
+    let widget = appearance(json!({ "opacity": 0.3 }));
+    assert_eq!(merge_appearance(&ThemeConfig::default(), &widget), widget);
+  }
+
+  fn container(id: &str, children: serde_json::Value) -> Widget {
+    serde_json::from_value(json!({
+      "id": id,
+      "description": "",
+      "widget_type": "Container",
+      "property": {},
+      "appearance": {},
+      "children": children,
+    }))
+    .unwrap()
+  }
+
+  fn child(id: &str) -> serde_json::Value {
+    json!({
+      "id": id,
+      "description": "",
+      "widget_type": "DefaultDateTime",
+      "property": {},
+      "appearance": {},
+    })
+  }
+
+  #[test]
+  fn validate_widget_tree_accepts_unique_ids() {
+    let widgets = [
+      container(
+        "sidebar",
+        json!([child("sidebar-clock"), child("sidebar-date")]),
+      ),
+      widget(json!({})),
+    ];
+
+    assert_eq!(validate_widget_tree(&widgets), Ok(()));
+  }
+
+  #[test]
+  fn validate_widget_tree_rejects_duplicate_children() {
+    let widgets = [container(
+      "sidebar",
+      json!([child("clock"), child("clock")]),
+    )];
+
+    assert_eq!(
+      validate_widget_tree(&widgets),
+      Err("duplicate widget ids: clock".to_string())
+    );
+  }
+
+  #[test]
+  fn validate_widget_tree_rejects_nested_duplicates() {
+    let nested = serde_json::to_value(container("inner", json!([child("clock")]))).unwrap();
+    let widgets = [
+      container("outer", json!([nested, child("inner")])),
+      widget(json!({})),
+    ];
+
+    // each id is listed once, however often it repeats
+    assert_eq!(
+      validate_widget_tree(&widgets),
+      Err("duplicate widget ids: clock, inner".to_string())
+    );
+  }
+}
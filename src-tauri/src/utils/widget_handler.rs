@@ -1,10 +1,85 @@
-use tauri::{webview::WebviewWindowBuilder, App, LogicalPosition, Manager, WindowBuilder};
+use std::{
+  collections::{BTreeMap, HashMap, HashSet},
+  path::{Path, PathBuf},
+  sync::{mpsc, Arc, Mutex},
+  time::{Duration, Instant},
+};
 
-use super::widget::{DefaultOrientation, Widget, WidgetType};
+use tauri::{
+  image::Image, webview::WebviewWindowBuilder, App, AppHandle, Emitter, LogicalPosition,
+  LogicalSize, Manager, Monitor, PhysicalPosition, Runtime, WebviewWindow, WindowEvent,
+};
+
+use super::{
+  appearance,
+  battery::{self, BatteryInfo, BatteryTracker},
+  bundle::{self, Bundle, ImportMode, BUNDLE_VERSION},
+  config, desktop,
+  disk_alerts::DiskAlerts,
+  error::WidgetError,
+  history::{self, Downsample, MetricHistory, MetricSeries, Point},
+  input, migration,
+  placement::{self, MonitorInfo, Rect},
+  processes::ProcessSampler,
+  profile::{apply_profile, capture_profile, ProfileStore},
+  rules::{self, AutoHideRule, Conditions},
+  settings::Settings,
+  snap::{self, SnapOptions},
+  subscription::MetricSubscription,
+  system::{self, SystemFetcher},
+  tray::{self, TrayItem},
+  widget::{
+    find_widget, merge_appearance, reassign_child_ids, unique_widget_id, validate_widget_id,
+    validate_widget_tree, widget_ids, ResolvedAppearance, ThemeConfig, Widget, WidgetLayer,
+    WidgetPlacement, WidgetType, WindowParams,
+  },
+};
+
+const GEOMETRY_DEBOUNCE: Duration = Duration::from_millis(500);
+const DISPLAY_POLL_INTERVAL: Duration = Duration::from_secs(2);
+const WINDOW_CLOSE_TIMEOUT: Duration = Duration::from_secs(1);
+const HOVER_POLL_INTERVAL: Duration = Duration::from_millis(100);
+const CONDITION_POLL_INTERVAL: Duration = Duration::from_secs(1);
+const DISK_ALERT_POLL_INTERVAL: Duration = Duration::from_secs(30);
+const BATTERY_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Default)]
+pub struct GeometryUpdate {
+  pub position: Option<(i32, i32)>,
+  pub placement: Option<WidgetPlacement>,
+  pub size: Option<(u32, u32)>,
+}
+
+impl GeometryUpdate {
+  fn merge(&mut self, other: GeometryUpdate) {
+    if other.position.is_some() {
+      self.position = other.position;
+    }
+    if other.placement.is_some() {
+      self.placement = other.placement;
+    }
+    if other.size.is_some() {
+      self.size = other.size;
+    }
+  }
+}
 
 #[derive(Clone)]
 pub struct WidgetHandler {
   pub widgets: Vec<Widget>,
+  pub config_dir: PathBuf,
+  pub sources: HashMap<String, PathBuf>,
+  pub theme: ThemeConfig,
+  pub profiles: ProfileStore,
+  pub settings: Settings,
+  conditions: Conditions,
+  geometry_writer: Option<mpsc::Sender<(String, GeometryUpdate)>>,
+  media_control_started: bool,
+  system_fetcher: Option<SystemFetcher>,
+  battery_subscribers: HashSet<String>,
+  batteries: Vec<BatteryInfo>,
+  history: Arc<Mutex<MetricHistory>>,
+  process_sampler: Arc<Mutex<ProcessSampler>>,
 }
 
 impl WidgetHandler {
@@ -19,102 +94,1459 @@ impl WidgetHandler {
         .unwrap_or_else(|_| panic!("failed to create config dir"));
     }
 
-    let config_path = config_path_dir.join("./widgets/");
+    let config_path = config::widgets_dir(&config_path_dir);
     if !config_path.exists() {
       std::fs::create_dir_all(&config_path)
         .unwrap_or_else(|_| panic!("failed to create widgets dir"));
     }
 
-    let num_widgets = config_path.read_dir().unwrap().count();
+    Self::load(&config_path_dir).unwrap_or_else(|e| panic!("{}", e))
+  }
+
+  // Reads every config from disk. Unlike the theme and profiles, a broken
+  // widget file is an error since there would be nothing sensible to show.
+  pub fn load(config_dir: &Path) -> Result<Self, String> {
+    let config_path = config::widgets_dir(config_dir);
+
+    let mut widgets = Vec::new();
+    let mut sources = HashMap::new();
 
-    if num_widgets == 0 {
-      Self {
-        widgets: Vec::new(),
+    // read all the JSON files in the widgets directory
+    let entries = config_path
+      .read_dir()
+      .map_err(|e| format!("failed to read widgets dir: {}", e))?;
+
+    for entry in entries {
+      let entry = entry.map_err(|e| format!("failed to read entry in widgets dir: {}", e))?;
+      let path = entry.path();
+      let file_name = path.file_name().unwrap().to_str().unwrap().to_string();
+      let file_stem = path.file_stem().unwrap().to_str().unwrap().to_string();
+
+      if path.is_file() && file_name.ends_with(".json") {
+        let mut raw = config::read_json(&path)?;
+        let version = migration::migrate_widget(&mut raw).map_err(|e| {
+          format!(
+            "failed to migrate config file: {} | Error: {}",
+            file_stem, e
+          )
+        })?;
+
+        if version < migration::SCHEMA_VERSION {
+          config::backup_file(&path, &format!("v{}", version))?;
+          config::write_json_preserving_format(&path, &raw)?;
+        }
+
+        let config: Widget = serde_json::from_value(raw).map_err(|e| {
+          format!(
+            "failed to parse config file: {} | Error: {:?}",
+            file_stem, e
+          )
+        })?;
+
+        sources.insert(config.id.clone(), path);
+        widgets.push(config);
       }
+    }
+
+    validate_widget_tree(&widgets).map_err(|e| format!("invalid widget configuration: {}", e))?;
+
+    let theme = config::load_theme(config_dir).unwrap_or_else(|e| {
+      eprintln!("Failed to load theme, using defaults: {}", e);
+      ThemeConfig::default()
+    });
+
+    let profiles = config::load_profiles(config_dir).unwrap_or_else(|e| {
+      eprintln!("Failed to load profiles, showing every widget: {}", e);
+      ProfileStore::default()
+    });
+
+    let settings = config::load_settings(config_dir).unwrap_or_else(|e| {
+      eprintln!("Failed to load settings, using defaults: {}", e);
+      Settings::default()
+    });
+
+    Ok(Self {
+      widgets,
+      config_dir: config_dir.to_path_buf(),
+      sources,
+      theme,
+      profiles,
+      settings,
+      conditions: Conditions::default(),
+      geometry_writer: None,
+      media_control_started: false,
+      system_fetcher: None,
+      battery_subscribers: HashSet::new(),
+      batteries: Vec::new(),
+      history: Arc::default(),
+      process_sampler: Arc::default(),
+    })
+  }
+
+  pub fn initialize_all_widgets(mut self, app: &mut App) {
+    let app_handle = app.handle().clone();
+    let monitors = monitor_infos(&app_handle);
+    self.geometry_writer = Some(spawn_geometry_writer(app_handle.clone()));
+
+    for widget in self.effective_widgets().iter() {
+      self
+        .open_window(&app_handle, widget, &monitors)
+        .unwrap_or_else(|e| {
+          eprintln!("Failed to open widget {}: {}", widget.id, e);
+          None
+        });
+    }
+
+    self.sync_media_control(&app_handle);
+
+    tray::create_tray(&app_handle, &self.tray_items()).unwrap_or_else(|e| {
+      eprintln!("Failed to create tray icon: {}", e);
+    });
+
+    app.manage(Mutex::new(self));
+    watch_displays(app_handle.clone(), monitors);
+    watch_hover_modifier(app_handle.clone());
+    watch_conditions(app_handle.clone());
+    watch_disk_space(app_handle.clone());
+    watch_battery(app_handle);
+  }
+
+  fn open_window<R: Runtime>(
+    &self,
+    app: &AppHandle<R>,
+    widget: &Widget,
+    monitors: &[MonitorInfo],
+  ) -> Result<Option<WebviewWindow<R>>, WidgetError> {
+    let Some(route) = widget.widget_type.route() else {
+      eprintln!(
+        "Skipping widget {}: {:?} widgets are not supported yet",
+        widget.id, widget.widget_type
+      );
+      return Ok(None);
+    };
+
+    let params = self.with_settings(widget).window_params();
+    let visible = self.window_visible(&params);
+    let (on_bottom, on_top) = params.layer.window_levels(self.conditions.fullscreen_app);
+
+    let mut window = WebviewWindowBuilder::new(
+      app,
+      widget.id.clone(),
+      tauri::WebviewUrl::App(format!("{}?id={}", route, &widget.id).into()),
+    )
+    .title(&params.title)
+    .decorations(false)
+    .transparent(true)
+    .skip_taskbar(true)
+    .always_on_bottom(on_bottom)
+    .always_on_top(on_top)
+    // placed widgets are shown once they have been moved into place
+    .visible(visible && widget.property.placement.is_none())
+    .resizable(params.resizable)
+    .inner_size(params.size.0, params.size.1)
+    .initialization_script(&appearance::appearance_script(
+      &self.resolve_appearance(widget),
+    ));
+
+    if let Some((x, y)) = params.position {
+      window = window.position(x, y);
     } else {
-      let mut widgets = Vec::new();
-
-      // read all the TOML files in the widgets directory
-      for entry in config_path.read_dir().unwrap() {
-        let entry = entry.unwrap_or_else(|_| panic!("failed to read entry in widgets dir"));
-        let path = entry.path();
-        let file_name = path.file_name().unwrap().to_str().unwrap().to_string();
-        let file_stem = path.file_stem().unwrap().to_str().unwrap().to_string();
-
-        if path.is_file() && file_name.ends_with(".json") {
-          let config: Widget = serde_json::from_str(&std::fs::read_to_string(&path).unwrap())
-            .unwrap_or_else(|e| {
-              panic!(
-                "failed to parse config file: {} | Error: {:?}",
-                file_stem, e
-              )
-            });
+      window = window.center();
+    }
+
+    if let Some(icon) = &params.icon {
+      match load_icon(&self.config_dir, icon) {
+        Ok(icon) => window = window.icon(icon)?,
+        Err(e) => eprintln!("Failed to load icon of widget {}: {}", widget.id, e),
+      }
+    }
+
+    let window = window.build()?;
+    if params.click_through {
+      window.set_ignore_cursor_events(true)?;
+    }
+    apply_placement(&window, widget, monitors).unwrap_or_else(|e| {
+      eprintln!("Failed to place widget {}: {}", widget.id, e);
+    });
+    if visible && !window.is_visible().unwrap_or(true) {
+      window.show()?;
+    }
+
+    if let Some(geometry_writer) = &self.geometry_writer {
+      watch_geometry(&window, geometry_writer.clone());
+    }
+
+    Ok(Some(window))
+  }
 
-          widgets.push(config);
+  // Applies an edited config to an already open window in place, so the
+  // webview keeps its state unless the widget type itself changed. The
+  // window follows the effective config, a widget the active profile leaves
+  // out stays closed.
+  fn refresh_window<R: Runtime>(
+    &self,
+    app: &AppHandle<R>,
+    previous: &Widget,
+    widget_id: &str,
+  ) -> Result<(), WidgetError> {
+    let effective = self.effective_widgets();
+    let Some(widget) = find_widget(&effective, widget_id) else {
+      return close_window(app, widget_id);
+    };
+
+    let Some(window) = app.get_webview_window(&widget.id) else {
+      self.open_window(app, widget, &monitor_infos(app))?;
+      return Ok(());
+    };
+
+    if previous.widget_type != widget.widget_type {
+      match widget.widget_type.route() {
+        Some(route) => {
+          let url = serde_json::to_string(&format!("{}?id={}", route, widget.id)).unwrap();
+          window.eval(&format!("window.location.replace({});", url))?;
+        }
+        None => {
+          window.destroy()?;
+          return Ok(());
         }
       }
+    }
 
-      Self { widgets }
+    let params = self.with_settings(widget).window_params();
+
+    window.set_title(&params.title)?;
+    window.set_resizable(params.resizable)?;
+    window.set_ignore_cursor_events(params.click_through)?;
+    self.apply_layer(&window, &params)?;
+    window.set_size(LogicalSize::new(params.size.0, params.size.1))?;
+
+    if let Some((x, y)) = params.position {
+      window.set_position(LogicalPosition::new(x, y))?;
+    }
+    apply_placement(&window, widget, &monitor_infos(app)).map_err(WidgetError::Window)?;
+
+    if let Some(icon) = &params.icon {
+      match load_icon(&self.config_dir, icon) {
+        Ok(icon) => window.set_icon(icon)?,
+        Err(e) => eprintln!("Failed to load icon of widget {}: {}", widget.id, e),
+      }
+    }
+
+    appearance::apply_appearance(&window, &self.resolve_appearance(widget))
+      .map_err(WidgetError::Window)?;
+
+    if self.window_visible(&params) {
+      window.show()?;
+    } else {
+      window.hide()?;
     }
+
+    Ok(())
   }
 
-  pub fn initialize_all_widgets(self, app: &mut App) {
-    let mut widget_handler = Self::new(app);
-    let app_handle = app.handle();
+  fn window_visible(&self, params: &WindowParams) -> bool {
+    params.visible && !rules::auto_hidden(&params.auto_hide, &self.conditions)
+  }
 
-    for widget in widget_handler.widgets.iter_mut() {
-      match widget.widget_type {
-        WidgetType::DefaultMediaPlayerControls => {
-          let mut window = WebviewWindowBuilder::new(
-            app_handle,
-            widget.id.clone(),
-            tauri::WebviewUrl::App(format!("/media?id={}", &widget.id).into()),
-          )
-          .decorations(false)
-          .transparent(true)
-          .skip_taskbar(true)
-          .always_on_bottom(true)
-          .resizable(widget.property.resizable.unwrap_or(false));
-
-          if widget.property.position.is_some() {
-            let position = widget.property.position.unwrap();
-            window = window.position(position.0 as f64, position.1 as f64);
-          } else {
-            window = window.center();
-          }
+  fn apply_layer<R: Runtime>(
+    &self,
+    window: &WebviewWindow<R>,
+    params: &WindowParams,
+  ) -> Result<(), WidgetError> {
+    let (on_bottom, on_top) = params.layer.window_levels(self.conditions.fullscreen_app);
 
-          if widget.property.orientation.is_some() {
-            let orientation = widget.property.orientation.clone().unwrap();
-            match orientation {
-              DefaultOrientation::Horizontal => {
-                window = window.inner_size(640.0, 160.0);
-              }
-              DefaultOrientation::Vertical => {
-                window = window.inner_size(320.0, 480.0);
-              }
-            }
-          } else {
-            window = window.inner_size(320.0, 480.0);
-          }
+    window.set_always_on_bottom(on_bottom)?;
+    window.set_always_on_top(on_top)?;
 
-          window.build().unwrap();
+    Ok(())
+  }
 
-          crate::utils::media::initiate_media_control(app).unwrap_or_else(|e| {
-            eprintln!("Failed to initiate media control: {}", e);
-          });
-        }
-        _ => todo!(),
+  // Other visible widgets and the monitor edges are snapped against; the
+  // snap settings are scaled to the monitor the window is on.
+  pub fn snap_rect<R: Runtime>(
+    &self,
+    app: &AppHandle<R>,
+    widget_id: &str,
+    rect: Rect,
+    monitors: &[MonitorInfo],
+  ) -> Rect {
+    let Some(settings) = &self.settings.snap else {
+      return rect;
+    };
+
+    let scale_factor = placement::monitor_containing(&rect, monitors)
+      .or_else(|| monitors.first())
+      .map_or(1.0, |monitor| monitor.scale_factor);
+    let options = SnapOptions {
+      grid: settings
+        .grid
+        .map(|grid| (grid as f64 * scale_factor).round() as u32),
+      threshold: (settings.threshold() as f64 * scale_factor).round() as u32,
+      edges: settings.edges(),
+    };
+
+    let others: Vec<Rect> = self
+      .effective_widgets()
+      .iter()
+      .filter(|widget| widget.id != widget_id)
+      .filter_map(|widget| app.get_webview_window(&widget.id))
+      .filter(|window| window.is_visible().unwrap_or(false))
+      .filter_map(|window| window_rect(&window))
+      .collect();
+    let bounds: Vec<Rect> = monitors.iter().map(|monitor| monitor.rect).collect();
+
+    snap::snap_rect(rect, &others, &bounds, &options)
+  }
+
+  // Whether any open widget depends on the conditions polled by
+  // `watch_conditions`, so nothing is polled when no rules are configured.
+  fn needs_conditions(&self) -> bool {
+    self.effective_widgets().iter().any(|widget| {
+      let params = widget.window_params();
+      !params.auto_hide.is_empty() || params.layer == WidgetLayer::Overlay
+    })
+  }
+
+  // Whether a battery widget is subscribed or a rule hides widgets on
+  // battery, so `watch_battery` doesn't poll when nothing uses the readings.
+  fn needs_battery(&self) -> bool {
+    !self.battery_subscribers.is_empty()
+      || self.effective_widgets().iter().any(|widget| {
+        widget
+          .window_params()
+          .auto_hide
+          .contains(&AutoHideRule::OnBattery)
+      })
+  }
+
+  // Re-evaluates the auto-hide rules and overlay layers once the polled
+  // conditions change.
+  pub fn apply_conditions<R: Runtime>(
+    &mut self,
+    app: &AppHandle<R>,
+    conditions: Conditions,
+  ) -> Result<(), WidgetError> {
+    if self.conditions == conditions {
+      return Ok(());
+    }
+    self.conditions = conditions;
+
+    for widget in self.effective_widgets() {
+      let Some(window) = app.get_webview_window(&widget.id) else {
+        continue;
+      };
+      let params = widget.window_params();
+
+      self.apply_layer(&window, &params)?;
+      if self.window_visible(&params) {
+        window.show()?;
+      } else {
+        window.hide()?;
       }
     }
 
-    app.manage(widget_handler);
+    Ok(())
+  }
+
+  // The fetcher only runs while a system monitor has a window, and only
+  // samples the metrics those monitors show. The battery is only polled for
+  // subscribed battery widgets (or OnBattery rules).
+  // Windows that close don't get to unsubscribe, so subscribers whose widget
+  // is gone are dropped whenever the widgets change.
+  fn sync_subscribers(&mut self) {
+    let ids = widget_ids(&self.effective_widgets());
+    self
+      .battery_subscribers
+      .retain(|subscriber| ids.iter().any(|id| id == subscriber));
+
+    let Some(fetcher) = &self.system_fetcher else {
+      return;
+    };
+    fetcher.retain(|subscriber| ids.iter().any(|id| id == subscriber));
+
+    if !fetcher.has_subscribers() {
+      fetcher.stop();
+      self.system_fetcher = None;
+      return;
+    }
+
+    // settings.json may have been edited and reloaded
+    fetcher.set_network_settings(self.settings.network.clone().unwrap_or_default());
+  }
+
+  pub fn subscribe_metrics<R: Runtime>(
+    &mut self,
+    app: &AppHandle<R>,
+    widget_id: &str,
+    window: &str,
+    subscriptions: &[MetricSubscription],
+  ) -> Result<(), WidgetError> {
+    if find_widget(&self.widgets, widget_id).is_none() {
+      return Err(WidgetError::NotFound(widget_id.to_string()));
+    }
+
+    if subscriptions.is_empty() {
+      self.unsubscribe_metrics(widget_id);
+      return Ok(());
+    }
+
+    let fetcher = match &self.system_fetcher {
+      Some(fetcher) => fetcher.clone(),
+      None => {
+        let fetcher = system::initiate_system_info_fetcher(app, self.history.clone())
+          .map_err(WidgetError::Window)?;
+        fetcher.set_network_settings(self.settings.network.clone().unwrap_or_default());
+        self.system_fetcher = Some(fetcher.clone());
+        fetcher
+      }
+    };
+    fetcher.subscribe(widget_id, window, subscriptions);
+
+    Ok(())
+  }
+
+  // The newest `samples` points of a series reduced to at most `points`.
+  pub fn metric_history(
+    &self,
+    series: MetricSeries,
+    samples: usize,
+    points: usize,
+    method: Downsample,
+  ) -> Vec<Point> {
+    let recorded = self.history.lock().unwrap().last(series, samples);
+    history::downsample(&recorded, points, method)
+  }
+
+  // Sampling processes is slow, so it happens outside the handler lock.
+  pub fn process_sampler(&self) -> Arc<Mutex<ProcessSampler>> {
+    self.process_sampler.clone()
+  }
+
+  pub fn unsubscribe_metrics(&mut self, widget_id: &str) {
+    if let Some(fetcher) = &self.system_fetcher {
+      fetcher.unsubscribe(widget_id);
+    }
+    self.sync_subscribers();
+  }
+
+  pub fn subscribe_battery(&mut self, widget_id: &str) -> Result<(), WidgetError> {
+    if find_widget(&self.widgets, widget_id).is_none() {
+      return Err(WidgetError::NotFound(widget_id.to_string()));
+    }
+
+    self.battery_subscribers.insert(widget_id.to_string());
+    Ok(())
+  }
+
+  pub fn unsubscribe_battery(&mut self, widget_id: &str) {
+    self.battery_subscribers.remove(widget_id);
+  }
+
+  fn sync_media_control<R: Runtime>(&mut self, app: &AppHandle<R>) {
+    // containers can hold media widgets too, so look through the whole tree
+    let needs_media_control = self
+      .widgets
+      .iter()
+      .any(|widget| widget.contains_type(&WidgetType::DefaultMediaPlayerControls));
+
+    if needs_media_control && !self.media_control_started {
+      crate::utils::media::initiate_media_control(app).unwrap_or_else(|e| {
+        eprintln!("Failed to initiate media control: {}", e);
+      });
+      self.media_control_started = true;
+    }
+  }
+
+  pub fn resolve_appearance(&self, widget: &Widget) -> ResolvedAppearance {
+    merge_appearance(&self.theme, &widget.appearance).resolve()
+  }
+
+  // The global toggles switch a property on for every widget without
+  // touching the config files.
+  fn with_settings(&self, widget: &Widget) -> Widget {
+    let mut widget = widget.clone();
+
+    if self.settings.locked() {
+      widget.property.locked = Some(true);
+    }
+    if self.settings.click_through() {
+      widget.property.click_through = Some(true);
+    }
+
+    widget
+  }
+
+  // The widgets that should have a window right now, with the overrides of
+  // the active profile and the global toggles applied.
+  pub fn effective_widgets(&self) -> Vec<Widget> {
+    let widgets = match self.profiles.active_profile() {
+      Some(profile) => apply_profile(&self.widgets, profile),
+      None => self.widgets.clone(),
+    };
+
+    widgets
+      .iter()
+      .map(|widget| self.with_settings(widget))
+      .collect()
   }
 
   pub fn get_widget(&self, widget_id: &str) -> Option<Widget> {
+    find_widget(&self.effective_widgets(), widget_id).cloned()
+  }
+
+  pub fn persist_geometry(
+    &mut self,
+    widget_id: &str,
+    update: &GeometryUpdate,
+  ) -> Result<(), String> {
+    // while a profile is active its layout is what gets dragged around
+    if let Some(entry) = self
+      .profiles
+      .active_profile_mut()
+      .and_then(|profile| profile.widgets.get_mut(widget_id))
+    {
+      let previous = entry.clone();
+      if update.position.is_some() {
+        entry.position = update.position;
+      }
+      if update.placement.is_some() {
+        entry.placement = update.placement.clone();
+      }
+      if update.size.is_some() {
+        entry.size = update.size;
+      }
+
+      if *entry != previous {
+        config::save_profiles(&self.config_dir, &self.profiles)?;
+      }
+      return Ok(());
+    }
+
+    let widget = self
+      .widgets
+      .iter_mut()
+      .find(|widget| widget.id == widget_id)
+      .ok_or_else(|| format!("unknown widget: {}", widget_id))?;
+
+    let position = update
+      .position
+      .filter(|position| widget.property.position != Some(*position));
+    let placement = update
+      .placement
+      .clone()
+      .filter(|placement| widget.property.placement.as_ref() != Some(placement));
+    let size = update
+      .size
+      .filter(|size| widget.property.size != Some(*size));
+
+    if position.is_none() && placement.is_none() && size.is_none() {
+      return Ok(());
+    }
+
+    let path = self
+      .sources
+      .get(widget_id)
+      .ok_or_else(|| format!("no config file for widget: {}", widget_id))?;
+
+    config::update_widget_geometry(path, position, placement.as_ref(), size)?;
+
+    if position.is_some() {
+      widget.property.position = position;
+    }
+    if placement.is_some() {
+      widget.property.placement = placement;
+    }
+    if size.is_some() {
+      widget.property.size = size;
+    }
+
+    Ok(())
+  }
+
+  fn save_widget(&self, path: &Path, widget: &Widget) -> Result<(), WidgetError> {
+    let mut raw =
+      serde_json::to_value(widget).map_err(|e| WidgetError::InvalidConfig(e.to_string()))?;
+    migration::set_schema_version(&mut raw).map_err(WidgetError::InvalidConfig)?;
+
+    config::write_json_preserving_format(path, &raw).map_err(WidgetError::Io)
+  }
+
+  fn source_of(&self, widget_id: &str) -> Result<PathBuf, WidgetError> {
     self
+      .sources
+      .get(widget_id)
+      .cloned()
+      .ok_or_else(|| WidgetError::NotFound(widget_id.to_string()))
+  }
+
+  pub fn list_widgets(&self) -> Vec<Widget> {
+    self.widgets.clone()
+  }
+
+  pub fn create_widget<R: Runtime>(
+    &mut self,
+    app: &AppHandle<R>,
+    widget: Widget,
+  ) -> Result<Widget, WidgetError> {
+    validate_widget_id(&widget.id).map_err(WidgetError::InvalidConfig)?;
+
+    if find_widget(&self.widgets, &widget.id).is_some() {
+      return Err(WidgetError::AlreadyExists(widget.id));
+    }
+
+    let mut widgets = self.widgets.clone();
+    widgets.push(widget.clone());
+    validate_widget_tree(&widgets).map_err(WidgetError::InvalidConfig)?;
+
+    let path = config::widgets_dir(&self.config_dir).join(format!("{}.json", widget.id));
+    if path.exists() {
+      return Err(WidgetError::AlreadyExists(widget.id));
+    }
+
+    self.save_widget(&path, &widget)?;
+    self.sources.insert(widget.id.clone(), path);
+    self.widgets.push(widget.clone());
+
+    self.open_window(app, &widget, &monitor_infos(app))?;
+    self.sync_media_control(app);
+    self.sync_subscribers();
+    self.sync_tray(app);
+
+    Ok(widget)
+  }
+
+  // Only top-level widgets can be updated directly; children are edited
+  // through their container.
+  pub fn update_widget<R: Runtime>(
+    &mut self,
+    app: &AppHandle<R>,
+    widget: Widget,
+  ) -> Result<Widget, WidgetError> {
+    let index = self
+      .widgets
+      .iter()
+      .position(|existing| existing.id == widget.id)
+      .ok_or_else(|| WidgetError::NotFound(widget.id.clone()))?;
+
+    let mut widgets = self.widgets.clone();
+    widgets[index] = widget.clone();
+    validate_widget_tree(&widgets).map_err(WidgetError::InvalidConfig)?;
+
+    self.save_widget(&self.source_of(&widget.id)?, &widget)?;
+    let previous = std::mem::replace(&mut self.widgets[index], widget.clone());
+
+    self.refresh_window(app, &previous, &widget.id)?;
+    self.sync_media_control(app);
+    self.sync_subscribers();
+    self.sync_tray(app);
+
+    Ok(widget)
+  }
+
+  pub fn duplicate_widget<R: Runtime>(
+    &mut self,
+    app: &AppHandle<R>,
+    widget_id: &str,
+    new_id: Option<String>,
+  ) -> Result<Widget, WidgetError> {
+    let mut widget = self
       .widgets
       .iter()
       .find(|widget| widget.id == widget_id)
       .cloned()
+      .ok_or_else(|| WidgetError::NotFound(widget_id.to_string()))?;
+
+    let mut taken = widget_ids(&self.widgets);
+    widget.id = new_id.unwrap_or_else(|| unique_widget_id(widget_id, &taken));
+    taken.push(widget.id.clone());
+    reassign_child_ids(&mut widget, &mut taken);
+
+    self.create_widget(app, widget)
+  }
+
+  pub fn delete_widget<R: Runtime>(
+    &mut self,
+    app: &AppHandle<R>,
+    widget_id: &str,
+  ) -> Result<(), WidgetError> {
+    let index = self
+      .widgets
+      .iter()
+      .position(|widget| widget.id == widget_id)
+      .ok_or_else(|| WidgetError::NotFound(widget_id.to_string()))?;
+
+    let path = self.source_of(widget_id)?;
+    std::fs::remove_file(&path)
+      .map_err(|e| WidgetError::Io(format!("failed to remove {}: {}", path.display(), e)))?;
+
+    self.widgets.remove(index);
+    self.sources.remove(widget_id);
+    self.sync_subscribers();
+    self.sync_tray(app);
+
+    close_window(app, widget_id)
+  }
+
+  pub fn set_widget_hidden<R: Runtime>(
+    &mut self,
+    app: &AppHandle<R>,
+    widget_id: &str,
+    hidden: bool,
+  ) -> Result<(), WidgetError> {
+    let path = self.source_of(widget_id)?;
+    let widget = self
+      .widgets
+      .iter_mut()
+      .find(|widget| widget.id == widget_id)
+      .ok_or_else(|| WidgetError::NotFound(widget_id.to_string()))?;
+
+    config::update_widget_property(&path, "hidden", serde_json::json!(hidden))
+      .map_err(WidgetError::Io)?;
+    widget.property.hidden = Some(hidden);
+    self.sync_tray(app);
+
+    let window = app
+      .get_webview_window(widget_id)
+      .ok_or_else(|| WidgetError::Window(format!("widget {} has no window", widget_id)))?;
+
+    // an auto-hidden widget stays hidden until its rules stop matching
+    let visible = self
+      .get_widget(widget_id)
+      .is_some_and(|widget| self.window_visible(&widget.window_params()));
+
+    if visible {
+      window.show()?;
+    } else {
+      window.hide()?;
+    }
+
+    Ok(())
+  }
+
+  pub fn list_profiles(&self) -> ProfileStore {
+    self.profiles.clone()
+  }
+
+  // Saves the current layout under `name`, keeping any appearance overrides
+  // an existing profile of that name already had.
+  pub fn save_profile<R: Runtime>(
+    &mut self,
+    app: &AppHandle<R>,
+    name: &str,
+  ) -> Result<(), WidgetError> {
+    let mut profile = capture_profile(&self.effective_widgets());
+
+    if let Some(existing) = self.profiles.profiles.get(name) {
+      for (widget_id, entry) in profile.widgets.iter_mut() {
+        if let Some(existing) = existing.widgets.get(widget_id) {
+          entry.appearance = existing.appearance.clone();
+        }
+      }
+    }
+
+    self.profiles.profiles.insert(name.to_string(), profile);
+    self.sync_tray(app);
+
+    config::save_profiles(&self.config_dir, &self.profiles).map_err(WidgetError::Io)
+  }
+
+  // Passing `None` leaves profile mode and shows every widget again.
+  pub fn switch_profile<R: Runtime>(
+    &mut self,
+    app: &AppHandle<R>,
+    name: Option<String>,
+  ) -> Result<(), WidgetError> {
+    if let Some(name) = &name {
+      if !self.profiles.profiles.contains_key(name) {
+        return Err(WidgetError::ProfileNotFound(name.clone()));
+      }
+    }
+
+    for widget in self.effective_widgets() {
+      close_window(app, &widget.id)?;
+    }
+
+    self.profiles.active = name;
+    config::save_profiles(&self.config_dir, &self.profiles).map_err(WidgetError::Io)?;
+
+    let monitors = monitor_infos(app);
+    for widget in self.effective_widgets() {
+      self.open_window(app, &widget, &monitors)?;
+    }
+    self.sync_subscribers();
+    self.sync_tray(app);
+
+    Ok(())
+  }
+
+  pub fn delete_profile<R: Runtime>(
+    &mut self,
+    app: &AppHandle<R>,
+    name: &str,
+  ) -> Result<(), WidgetError> {
+    if !self.profiles.profiles.contains_key(name) {
+      return Err(WidgetError::ProfileNotFound(name.to_string()));
+    }
+
+    if self.profiles.active.as_deref() == Some(name) {
+      self.switch_profile(app, None)?;
+    }
+
+    self.profiles.profiles.remove(name);
+    self.sync_tray(app);
+
+    config::save_profiles(&self.config_dir, &self.profiles).map_err(WidgetError::Io)
+  }
+
+  pub fn set_locked<R: Runtime>(
+    &mut self,
+    app: &AppHandle<R>,
+    locked: bool,
+  ) -> Result<(), WidgetError> {
+    self.settings.locked = Some(locked);
+    config::save_settings(&self.config_dir, &self.settings).map_err(WidgetError::Io)?;
+
+    for widget in self.effective_widgets() {
+      if let Some(window) = app.get_webview_window(&widget.id) {
+        window.set_resizable(widget.window_params().resizable)?;
+      }
+    }
+
+    // widgets fetch their config again to pick up the drag region change
+    app.emit("widgetsLocked", locked)?;
+    self.sync_tray(app);
+
+    Ok(())
+  }
+
+  pub fn set_click_through<R: Runtime>(
+    &mut self,
+    app: &AppHandle<R>,
+    click_through: bool,
+  ) -> Result<(), WidgetError> {
+    self.settings.click_through = Some(click_through);
+    config::save_settings(&self.config_dir, &self.settings).map_err(WidgetError::Io)?;
+
+    for widget in self.effective_widgets() {
+      if let Some(window) = app.get_webview_window(&widget.id) {
+        window.set_ignore_cursor_events(widget.window_params().click_through)?;
+      }
+    }
+    self.sync_tray(app);
+
+    Ok(())
+  }
+
+  // Picks up configs edited by hand. Nothing is replaced unless every widget
+  // file parses, so a typo doesn't tear down the running widgets.
+  pub fn reload<R: Runtime>(&mut self, app: &AppHandle<R>) -> Result<(), WidgetError> {
+    let loaded = Self::load(&self.config_dir).map_err(WidgetError::InvalidConfig)?;
+
+    for widget in self.effective_widgets() {
+      close_window(app, &widget.id)?;
+    }
+
+    self.widgets = loaded.widgets;
+    self.sources = loaded.sources;
+    self.theme = loaded.theme;
+    self.profiles = loaded.profiles;
+    self.settings = loaded.settings;
+
+    let monitors = monitor_infos(app);
+    for widget in self.effective_widgets() {
+      self.open_window(app, &widget, &monitors)?;
+    }
+    self.sync_media_control(app);
+    self.sync_subscribers();
+    self.sync_tray(app);
+
+    Ok(())
+  }
+
+  pub fn export_bundle(&self, path: &Path) -> Result<(), WidgetError> {
+    // the files are bundled as they are on disk, including unknown fields
+    let widgets = self
+      .widgets
+      .iter()
+      .map(|widget| config::read_json(&self.source_of(&widget.id)?).map_err(WidgetError::Io))
+      .collect::<Result<Vec<_>, _>>()?;
+
+    let theme_path = config::theme_path(&self.config_dir);
+    let theme = if theme_path.exists() {
+      Some(config::read_json(&theme_path).map_err(WidgetError::Io)?)
+    } else {
+      None
+    };
+
+    let mut assets = BTreeMap::new();
+    for asset in bundle::asset_paths(&self.widgets) {
+      let file = self.config_dir.join(&asset);
+      match std::fs::read(&file) {
+        Ok(contents) => {
+          assets.insert(asset, bundle::encode_asset(&contents));
+        }
+        Err(e) => eprintln!("Failed to bundle asset {}: {}", file.display(), e),
+      }
+    }
+
+    let bundle = Bundle {
+      version: BUNDLE_VERSION,
+      widgets,
+      theme,
+      assets,
+    };
+
+    let raw =
+      serde_json::to_value(&bundle).map_err(|e| WidgetError::InvalidConfig(e.to_string()))?;
+    let contents = config::to_json_string(&raw, "  ").map_err(WidgetError::Io)?;
+
+    config::atomic_write(path, contents.as_bytes()).map_err(WidgetError::Io)
   }
+
+  // Everything is checked before the first file is written, so a bad bundle
+  // leaves the current setup alone.
+  pub fn import_bundle<R: Runtime>(
+    &mut self,
+    app: &AppHandle<R>,
+    path: &Path,
+    mode: ImportMode,
+  ) -> Result<Vec<Widget>, WidgetError> {
+    let raw = config::read_json(path).map_err(WidgetError::Io)?;
+    let bundle = bundle::migrate_bundle(raw).map_err(WidgetError::InvalidConfig)?;
+
+    let mut widgets = bundle
+      .widgets
+      .into_iter()
+      .map(|mut raw| {
+        migration::migrate_widget(&mut raw).map_err(WidgetError::InvalidConfig)?;
+        serde_json::from_value::<Widget>(raw)
+          .map_err(|e| WidgetError::InvalidConfig(format!("failed to parse widget: {}", e)))
+      })
+      .collect::<Result<Vec<_>, _>>()?;
+
+    let mut existing = self.widgets.clone();
+    match mode {
+      ImportMode::Rename => {
+        let mut taken = widget_ids(&self.widgets);
+        for widget in widgets.iter_mut() {
+          bundle::rename_colliding_ids(widget, &mut taken);
+        }
+      }
+      ImportMode::Overwrite => {
+        existing.retain(|widget| !widgets.iter().any(|imported| imported.id == widget.id));
+      }
+    }
+
+    for widget in widgets.iter() {
+      validate_widget_id(&widget.id).map_err(WidgetError::InvalidConfig)?;
+    }
+    existing.extend(widgets.iter().cloned());
+    validate_widget_tree(&existing).map_err(WidgetError::InvalidConfig)?;
+
+    let mut assets = bundle
+      .assets
+      .iter()
+      .map(|(asset, encoded)| {
+        bundle::asset_path(asset).map_err(WidgetError::InvalidConfig)?;
+        let contents = bundle::decode_asset(encoded).map_err(WidgetError::InvalidConfig)?;
+        Ok((asset.clone(), contents))
+      })
+      .collect::<Result<Vec<_>, WidgetError>>()?;
+
+    // renaming never replaces a different file that is already there, the
+    // asset is written next to it and the imported icons follow
+    if mode == ImportMode::Rename {
+      let mut renamed = BTreeMap::new();
+      for (asset, contents) in assets.iter_mut() {
+        let path = self.config_dir.join(&*asset);
+        if !path.exists() || std::fs::read(&path).is_ok_and(|existing| existing == *contents) {
+          continue;
+        }
+
+        let new_path = bundle::unique_asset_path(asset, |candidate| {
+          self.config_dir.join(candidate).exists()
+            || bundle.assets.contains_key(candidate)
+            || renamed.values().any(|taken| taken == candidate)
+        });
+        renamed.insert(asset.clone(), new_path.clone());
+        *asset = new_path;
+      }
+
+      for widget in widgets.iter_mut() {
+        bundle::rename_icons(widget, &renamed);
+      }
+    }
+
+    for (asset, contents) in assets {
+      let path = self.config_dir.join(asset);
+      if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+          .map_err(|e| WidgetError::Io(format!("failed to create {}: {}", parent.display(), e)))?;
+      }
+      std::fs::write(&path, contents)
+        .map_err(|e| WidgetError::Io(format!("failed to write {}: {}", path.display(), e)))?;
+    }
+
+    if let Some(theme) = bundle.theme {
+      let theme_path = config::theme_path(&self.config_dir);
+      let theme = match (mode, theme_path.exists()) {
+        (ImportMode::Rename, true) => {
+          let mut existing = config::read_json(&theme_path).map_err(WidgetError::Io)?;
+          bundle::merge_theme(&mut existing, theme);
+          existing
+        }
+        _ => theme,
+      };
+      config::write_json_preserving_format(&theme_path, &theme).map_err(WidgetError::Io)?;
+    }
+
+    for widget in widgets.iter() {
+      let path = match self.sources.get(&widget.id) {
+        Some(path) if mode == ImportMode::Overwrite => path.clone(),
+        _ => config::widgets_dir(&self.config_dir).join(format!("{}.json", widget.id)),
+      };
+      self.save_widget(&path, widget)?;
+    }
+
+    self.reload(app)?;
+
+    Ok(widgets)
+  }
+
+  pub fn tray_items(&self) -> Vec<TrayItem> {
+    tray::tray_items(
+      &self.widgets,
+      &self.effective_widgets(),
+      &self.profiles,
+      &self.settings,
+    )
+  }
+
+  pub fn sync_tray<R: Runtime>(&self, app: &AppHandle<R>) {
+    tray::update_tray(app, &self.tray_items()).unwrap_or_else(|e| {
+      eprintln!("Failed to update tray menu: {}", e);
+    });
+  }
+}
+
+// Window labels are only released once the event loop has processed the
+// destroy, so wait for that before a window with the same label is rebuilt.
+// Must not be called from the main thread.
+fn close_window<R: Runtime>(app: &AppHandle<R>, widget_id: &str) -> Result<(), WidgetError> {
+  let Some(window) = app.get_webview_window(widget_id) else {
+    return Ok(());
+  };
+
+  window.destroy()?;
+
+  let deadline = Instant::now() + WINDOW_CLOSE_TIMEOUT;
+  while app.get_webview_window(widget_id).is_some() && Instant::now() < deadline {
+    std::thread::sleep(Duration::from_millis(10));
+  }
+
+  Ok(())
+}
+
+// Icons are decoded with the `image` crate so any format it supports works;
+// relative paths are resolved against the app config dir.
+fn load_icon(config_dir: &Path, icon: &str) -> Result<Image<'static>, String> {
+  let path = config_dir.join(icon);
+  let rgba = image::open(&path)
+    .map_err(|e| format!("failed to open {}: {}", path.display(), e))?
+    .into_rgba8();
+  let (width, height) = rgba.dimensions();
+
+  Ok(Image::new_owned(rgba.into_raw(), width, height))
+}
+
+// Collects move/resize events from every widget window and writes them back
+// to the config files once a widget has been still for `GEOMETRY_DEBOUNCE`.
+fn spawn_geometry_writer<R: Runtime>(app: AppHandle<R>) -> mpsc::Sender<(String, GeometryUpdate)> {
+  let (tx, rx) = mpsc::channel::<(String, GeometryUpdate)>();
+
+  std::thread::spawn(move || {
+    let mut pending: HashMap<String, (GeometryUpdate, Instant)> = HashMap::new();
+
+    loop {
+      let disconnected = match rx.recv_timeout(GEOMETRY_DEBOUNCE) {
+        Ok((widget_id, update)) => {
+          let entry = pending
+            .entry(widget_id)
+            .or_insert_with(|| (GeometryUpdate::default(), Instant::now()));
+          entry.0.merge(update);
+          entry.1 = Instant::now();
+          false
+        }
+        Err(mpsc::RecvTimeoutError::Timeout) => false,
+        Err(mpsc::RecvTimeoutError::Disconnected) => true,
+      };
+
+      // the handler is only managed once every window has been built
+      let Some(state) = app.try_state::<Mutex<WidgetHandler>>() else {
+        if disconnected {
+          break;
+        }
+        continue;
+      };
+
+      // a drag that pauses with the button still down hasn't ended yet
+      let dragging = input::mouse_button_held();
+      let ready: Vec<String> = pending
+        .iter()
+        .filter(|(_, (_, changed_at))| {
+          disconnected || (!dragging && changed_at.elapsed() >= GEOMETRY_DEBOUNCE)
+        })
+        .map(|(widget_id, _)| widget_id.clone())
+        .collect();
+
+      for widget_id in ready {
+        let (mut update, _) = pending.remove(&widget_id).unwrap();
+        let mut widget_handler = state.lock().unwrap();
+
+        // only user drags get this far, and a resize from the top or left
+        // edge moves the window too but must keep the size it was dragged to
+        if update.position.is_some() && update.size.is_none() {
+          if let Some(window) = app.get_webview_window(&widget_id) {
+            snap_dropped_window(&app, &widget_handler, &window, &mut update);
+          }
+        }
+
+        widget_handler
+          .persist_geometry(&widget_id, &update)
+          .unwrap_or_else(|e| {
+            eprintln!("Failed to save geometry of widget {}: {}", widget_id, e);
+          });
+      }
+
+      if disconnected {
+        break;
+      }
+    }
+  });
+
+  tx
+}
+
+// Runs once a dragged widget has come to rest: moves it onto the grid or
+// against nearby edges, and turns the final position into a placement offset
+// for placed widgets, which keep their anchor.
+fn snap_dropped_window<R: Runtime>(
+  app: &AppHandle<R>,
+  widget_handler: &WidgetHandler,
+  window: &WebviewWindow<R>,
+  update: &mut GeometryUpdate,
+) {
+  let Some(rect) = window_rect(window) else {
+    return;
+  };
+  let monitors = monitor_infos(app);
+
+  let snapped = widget_handler.snap_rect(app, window.label(), rect, &monitors);
+  if snapped != rect {
+    window
+      .set_position(PhysicalPosition::new(snapped.x, snapped.y))
+      .unwrap_or_else(|e| {
+        eprintln!("Failed to snap widget {}: {}", window.label(), e);
+      });
+
+    let scale_factor = window.scale_factor().unwrap_or(1.0);
+    update.position = Some((
+      (snapped.x as f64 / scale_factor).round() as i32,
+      (snapped.y as f64 / scale_factor).round() as i32,
+    ));
+  }
+
+  let anchor = widget_handler
+    .get_widget(window.label())
+    .and_then(|widget| widget.property.placement)
+    .map(|placement| placement.anchor);
+  if let Some(anchor) = anchor {
+    update.position = None;
+    update.placement = placement::placement_from_rect(snapped, &monitors, anchor);
+  }
+}
+
+fn watch_geometry<R: Runtime>(
+  window: &WebviewWindow<R>,
+  geometry_writer: mpsc::Sender<(String, GeometryUpdate)>,
+) {
+  let widget_id = window.label().to_string();
+  let window_clone = window.clone();
+
+  window.on_window_event(move |event| {
+    let scale_factor = window_clone.scale_factor().unwrap_or(1.0);
+
+    let update = match event {
+      WindowEvent::Moved(position) => {
+        let position = position.to_logical::<f64>(scale_factor);
+        GeometryUpdate {
+          position: Some((position.x.round() as i32, position.y.round() as i32)),
+          ..Default::default()
+        }
+      }
+      // minimized windows report a zero size on some platforms
+      WindowEvent::Resized(size) if size.width > 0 && size.height > 0 => {
+        let size = size.to_logical::<f64>(scale_factor);
+        GeometryUpdate {
+          size: Some((size.width.round() as u32, size.height.round() as u32)),
+          ..Default::default()
+        }
+      }
+      _ => return,
+    };
+
+    // only what the user dragged is saved, re-placing a widget after a
+    // display change must not overwrite where it belongs
+    if !input::mouse_button_held() {
+      return;
+    }
+
+    geometry_writer
+      .send((widget_id.clone(), update))
+      .unwrap_or_else(|e| {
+        eprintln!("Failed to queue geometry update: {}", e);
+      });
+  });
+}
+
+fn monitor_info(monitor: &Monitor) -> MonitorInfo {
+  MonitorInfo {
+    name: monitor.name().cloned(),
+    rect: Rect {
+      x: monitor.position().x,
+      y: monitor.position().y,
+      width: monitor.size().width,
+      height: monitor.size().height,
+    },
+    scale_factor: monitor.scale_factor(),
+  }
+}
+
+// Primary monitor first, which is what `placement` falls back to.
+fn monitor_infos<R: Runtime>(app: &AppHandle<R>) -> Vec<MonitorInfo> {
+  let primary = app
+    .primary_monitor()
+    .ok()
+    .flatten()
+    .map(|monitor| monitor_info(&monitor));
+
+  let mut monitors: Vec<MonitorInfo> = app
+    .available_monitors()
+    .unwrap_or_default()
+    .iter()
+    .map(monitor_info)
+    .collect();
+
+  if let Some(primary) = primary {
+    if let Some(index) = monitors.iter().position(|monitor| *monitor == primary) {
+      let primary = monitors.remove(index);
+      monitors.insert(0, primary);
+    }
+  }
+
+  monitors
+}
+
+fn window_rect<R: Runtime>(window: &WebviewWindow<R>) -> Option<Rect> {
+  let position = window.outer_position().ok()?;
+  let size = window.outer_size().ok()?;
+
+  Some(Rect {
+    x: position.x,
+    y: position.y,
+    width: size.width,
+    height: size.height,
+  })
+}
+
+fn apply_placement<R: Runtime>(
+  window: &WebviewWindow<R>,
+  widget: &Widget,
+  monitors: &[MonitorInfo],
+) -> Result<(), String> {
+  let current = window_rect(window);
+
+  let target = match &widget.property.placement {
+    Some(placement) => {
+      placement::resolve_placement(placement, monitors, widget.window_params().size)
+    }
+    None => current.map(|rect| placement::clamp_to_monitors(rect, monitors)),
+  };
+
+  match target {
+    Some(rect) if Some(rect) != current => window
+      .set_position(PhysicalPosition::new(rect.x, rect.y))
+      .map_err(|e| e.to_string()),
+    _ => Ok(()),
+  }
+}
+
+// Tauri has no display change event, so the monitor layout is polled and every
+// widget is placed again whenever it changes.
+fn watch_displays<R: Runtime>(app: AppHandle<R>, mut monitors: Vec<MonitorInfo>) {
+  std::thread::spawn(move || loop {
+    std::thread::sleep(DISPLAY_POLL_INTERVAL);
+
+    let current = monitor_infos(&app);
+    if current == monitors || current.is_empty() {
+      continue;
+    }
+    monitors = current;
+
+    let widgets = app
+      .state::<Mutex<WidgetHandler>>()
+      .lock()
+      .unwrap()
+      .effective_widgets();
+
+    for widget in widgets.iter() {
+      if let Some(window) = app.get_webview_window(&widget.id) {
+        apply_placement(&window, widget, &monitors).unwrap_or_else(|e| {
+          eprintln!("Failed to place widget {}: {}", widget.id, e);
+        });
+      }
+    }
+  });
+}
+
+// Click-through windows get no mouse events at all, so hovering is detected by
+// comparing the cursor with the window bounds while the modifier is held.
+fn watch_hover_modifier<R: Runtime>(app: AppHandle<R>) {
+  std::thread::spawn(move || {
+    let mut interactive: HashSet<String> = HashSet::new();
+
+    loop {
+      std::thread::sleep(HOVER_POLL_INTERVAL);
+
+      // skip a round instead of waiting while the handler is busy
+      let (modifier, widgets) = {
+        let state = app.state::<Mutex<WidgetHandler>>();
+        let Ok(widget_handler) = state.try_lock() else {
+          continue;
+        };
+        // the widgets are only needed while a modifier is set
+        let Some(modifier) = widget_handler.settings.hover_modifier else {
+          interactive.clear();
+          continue;
+        };
+        (modifier, widget_handler.effective_widgets())
+      };
+
+      let modifier_held = input::modifier_held(modifier);
+
+      for widget in widgets
+        .iter()
+        .filter(|widget| widget.window_params().click_through)
+      {
+        let Some(window) = app.get_webview_window(&widget.id) else {
+          continue;
+        };
+
+        let hovered = modifier_held
+          && window_rect(&window)
+            .zip(window.cursor_position().ok())
+            .is_some_and(|(rect, cursor)| {
+              rect.contains_point((cursor.x.round() as i32, cursor.y.round() as i32))
+            });
+
+        let ignore = !hovered;
+        if ignore != interactive.contains(&widget.id) {
+          continue;
+        }
+
+        window.set_ignore_cursor_events(ignore).unwrap_or_else(|e| {
+          eprintln!(
+            "Failed to update cursor events of widget {}: {}",
+            widget.id, e
+          );
+        });
+
+        if ignore {
+          interactive.remove(&widget.id);
+        } else {
+          interactive.insert(widget.id.clone());
+        }
+      }
+    }
+  });
+}
+
+fn watch_conditions<R: Runtime>(app: AppHandle<R>) {
+  std::thread::spawn(move || loop {
+    std::thread::sleep(CONDITION_POLL_INTERVAL);
+
+    let state = app.state::<Mutex<WidgetHandler>>();
+    if !state.lock().unwrap().needs_conditions() {
+      continue;
+    }
+
+    // the battery is read by `watch_battery`, which polls whenever an
+    // OnBattery rule needs it
+    let on_battery = battery::on_battery(&state.lock().unwrap().batteries);
+    let conditions = Conditions {
+      fullscreen_app: desktop::fullscreen_app_focused(),
+      on_battery,
+    };
+    state
+      .lock()
+      .unwrap()
+      .apply_conditions(&app, conditions)
+      .unwrap_or_else(|e| {
+        eprintln!("Failed to apply auto-hide rules: {}", e);
+      });
+  });
+}
+
+// Runs on its own rather than in the system fetcher so low space is reported
+// without a system monitor open.
+fn watch_disk_space<R: Runtime>(app: AppHandle<R>) {
+  std::thread::spawn(move || {
+    let mut disks = sysinfo::Disks::new();
+    let mut alerts = DiskAlerts::default();
+
+    loop {
+      let state = app.state::<Mutex<WidgetHandler>>();
+      let settings = state.lock().unwrap().settings.disk_alerts.clone();
+
+      if let Some(settings) = settings {
+        disks.refresh_list();
+
+        for alert in alerts.update(&system::get_disk_info(&disks), &settings) {
+          app.emit("diskAlert", alert).unwrap_or_else(|e| {
+            eprintln!("Failed to emit disk alert event: {}", e);
+          });
+        }
+      }
+
+      std::thread::sleep(DISK_ALERT_POLL_INTERVAL);
+    }
+  });
+}
+
+// Battery widgets get an event when the charging state changes or the charge
+// moves by a percent, instead of polling themselves.
+fn watch_battery<R: Runtime>(app: AppHandle<R>) {
+  std::thread::spawn(move || {
+    let mut tracker = BatteryTracker::default();
+
+    loop {
+      let state = app.state::<Mutex<WidgetHandler>>();
+      if !state.lock().unwrap().needs_battery() {
+        // start over so the first reading after a pause is sent again
+        tracker = BatteryTracker::default();
+        state.lock().unwrap().batteries.clear();
+        std::thread::sleep(BATTERY_POLL_INTERVAL);
+        continue;
+      }
+
+      if let Some(batteries) = tracker.update(battery::batteries()) {
+        state.lock().unwrap().batteries = batteries.to_vec();
+        app.emit("batteryChanged", batteries).unwrap_or_else(|e| {
+          eprintln!("Failed to emit battery event: {}", e);
+        });
+      }
+
+      std::thread::sleep(BATTERY_POLL_INTERVAL);
+    }
+  });
 }
@@ -25,3 +25,208 @@ export interface IMediaControlEventPayload {
   app_id: string;
   main_color: Array<number>;
 }
+
+export type WidgetAnchor =
+  | "TopLeft"
+  | "Top"
+  | "TopRight"
+  | "Left"
+  | "Center"
+  | "Right"
+  | "BottomLeft"
+  | "Bottom"
+  | "BottomRight";
+
+export interface IWidgetPlacement {
+  monitor?: number | string;
+  anchor: WidgetAnchor;
+  offset?: [number, number];
+}
+
+export type WidgetLayer = "Desktop" | "Normal" | "Top" | "Overlay";
+
+export interface IWidgetProperty {
+  title?: string;
+  icon?: string;
+  hidden?: boolean;
+  resizable?: boolean;
+  draggable?: boolean;
+  position?: [number, number];
+  placement?: IWidgetPlacement;
+  size?: [number, number];
+  orientation?: "Horizontal" | "Vertical";
+  click_through?: boolean;
+  locked?: boolean;
+  layer?: WidgetLayer;
+  auto_hide?: Array<"FullscreenApp" | "OnBattery">;
+}
+
+export interface IWidgetLayout {
+  direction?: "Row" | "Column" | "Grid";
+  columns?: number;
+  gap?: number;
+  align?: "Start" | "Center" | "End" | "Stretch";
+  justify?: "Start" | "Center" | "End" | "Stretch";
+}
+
+export interface IWidget {
+  id: string;
+  description: string;
+  widget_type: string;
+  property: IWidgetProperty;
+  appearance: Record<string, unknown>;
+  layout?: IWidgetLayout;
+  metrics?: Array<SystemMetric>;
+  metric_intervals?: Partial<Record<SystemMetric, number>>;
+  processes?: IProcessList;
+  children?: Array<IWidget>;
+}
+
+export type SystemMetric = "Cpu" | "Ram" | "Disk" | "Network" | "Gpu" | "Sensors";
+
+export interface IRamInfo {
+  total: number;
+  used: number;
+  free: number;
+  avail: number;
+}
+
+export interface IDiskInfo {
+  name: string;
+  kind: string;
+  fs: string;
+  mount_point: string;
+  total_space: number;
+  available_space: number;
+  device: string | null;
+}
+
+export interface IDiskIo {
+  device: string;
+  partitions: Array<string>;
+  read_bytes_per_sec: number;
+  written_bytes_per_sec: number;
+  reads_per_sec: number;
+  writes_per_sec: number;
+}
+
+export interface IDiskAlert {
+  mount_point: string;
+  available_space: number;
+  total_space: number;
+  low: boolean;
+}
+
+export type ChargingState = "Charging" | "Discharging" | "Full" | "NotCharging" | "Unknown";
+
+export interface IBatteryInfo {
+  name: string;
+  percentage: number;
+  state: ChargingState;
+  // seconds
+  time_to_empty: number | null;
+  time_to_full: number | null;
+  health: number | null;
+  power_draw_watts: number | null;
+}
+
+export interface IGpuInfo {
+  id: string;
+  memory_usage: number | null;
+  encoder_usage: number | null;
+  decoder_usage: number | null;
+  global_utilization: number;
+}
+
+export type GpuSupport = "Supported" | "Unsupported";
+
+export interface ITemperature {
+  label: string;
+  celsius: number;
+  max: number | null;
+  critical: number | null;
+}
+
+export interface IFan {
+  label: string;
+  rpm: number;
+}
+
+export interface ISensorReadings {
+  temperatures: Array<ITemperature>;
+  fans: Array<IFan>;
+}
+
+export interface IChangingMachineInfo {
+  cpu_usage: Array<number> | null;
+  gpu_usage: Array<IGpuInfo> | null;
+  gpu_support: GpuSupport | null;
+  ram_usage: IRamInfo | null;
+  disk_usage: Array<IDiskInfo> | null;
+  disk_io: Array<IDiskIo> | null;
+  sensors: ISensorReadings | null;
+}
+
+export interface INetworkRates {
+  received_per_sec: number;
+  transmitted_per_sec: number;
+  packets_received_per_sec: number;
+  packets_transmitted_per_sec: number;
+}
+
+export interface INetworkInfo extends INetworkRates {
+  received: number;
+  total_received: number;
+  transmitted: number;
+  total_transmitted: number;
+  mac_address: string;
+}
+
+export interface INetworkSummary {
+  interfaces: Record<string, INetworkInfo>;
+  total: INetworkRates;
+}
+
+export interface IMetricSubscription {
+  metric: SystemMetric;
+  interval_ms: number;
+}
+
+export interface ISystemSample extends IChangingMachineInfo {
+  subscriber: string;
+  network: INetworkSummary | null;
+}
+
+export type MetricSeries =
+  | { Cpu: number }
+  | "RamUsed"
+  | "NetworkReceived"
+  | "NetworkTransmitted"
+  | "DiskRead"
+  | "DiskWritten";
+
+export type Downsample = "Min" | "Max" | "Avg" | "Lttb";
+
+export interface IPoint {
+  time: number;
+  value: number;
+}
+
+export type ProcessSort = "Cpu" | "Memory" | "DiskIo";
+
+export interface IProcessList {
+  sort?: ProcessSort;
+  count?: number;
+  interval_ms?: number;
+}
+
+export interface IProcessInfo {
+  pid: number;
+  name: string;
+  user: string | null;
+  command: Array<string>;
+  cpu_usage: number;
+  memory: number;
+  disk_read: number;
+  disk_written: number;
+}
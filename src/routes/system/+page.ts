@@ -0,0 +1,4 @@
+export async function load({ params, url }) {
+  let id = url.searchParams.get("id");
+  return { id };
+}